@@ -0,0 +1,556 @@
+use crate::grid::{bounded_neighbours, checked_position};
+use crate::{Arr2d, Arr2dError, Edges, Grid, GridMut, Neighbourhood};
+
+use std::ops::Range;
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A grid of booleans packed 64 to a word. Each row starts on a new word, so a row of up to 64
+/// cells takes a single `u64`. Positions beyond the edges have no neighbours.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// A grid with every position false.
+    pub fn new(height: usize, width: usize) -> BitGrid {
+        let words_per_row = width.div_ceil(WORD_BITS);
+        BitGrid {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height],
+        }
+    }
+
+    pub fn from_arr2d(grid: &Arr2d<bool>) -> BitGrid {
+        let mut bits = BitGrid::new(grid.height(), grid.width());
+        for cell in grid.all_cells().filter(|c| c.value()) {
+            bits.set(cell.row(), cell.column(), true);
+        }
+        bits
+    }
+
+    pub fn to_arr2d(&self) -> Arr2d<bool> {
+        Arr2d::from_fn(self.height, self.width, |row, column| self.get(row, column))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    fn locate(&self, row: usize, column: usize) -> (usize, u64) {
+        if let Err(e) = checked_position(row as isize, column as isize, (self.height, self.width)) {
+            panic!("{e}");
+        }
+        (
+            row * self.words_per_row + column / WORD_BITS,
+            1 << (column % WORD_BITS),
+        )
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the grid.
+    pub fn get(&self, row: usize, column: usize) -> bool {
+        let (word, mask) = self.locate(row, column);
+        self.words[word] & mask != 0
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the grid.
+    pub fn set(&mut self, row: usize, column: usize, value: bool) {
+        let (word, mask) = self.locate(row, column);
+        if value {
+            self.words[word] |= mask;
+        } else {
+            self.words[word] &= !mask;
+        }
+    }
+
+    /// The number of positions that are true.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn row_words(&self, row: usize) -> &[u64] {
+        &self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    /// The bits of the last word in each row that hold positions, the rest being padding.
+    fn last_word_mask(&self) -> u64 {
+        match self.width % WORD_BITS {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+
+    /// The number of true positions in `columns` of a row, counted a word at a time.
+    fn count_in_row(&self, row: usize, columns: Range<usize>) -> u32 {
+        let words = self.row_words(row);
+        let mut count = 0;
+        let mut column = columns.start;
+        while column < columns.end {
+            let word = column / WORD_BITS;
+            let from = column % WORD_BITS;
+            let to = (columns.end - word * WORD_BITS).min(WORD_BITS);
+            count += (words[word] & bit_range(from, to)).count_ones();
+            column = word * WORD_BITS + to;
+        }
+        count
+    }
+
+    /// The number of true positions among the eight surrounding one, treating everything beyond
+    /// the edges as false.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the grid.
+    pub fn neighbour_count(&self, row: usize, column: usize) -> u32 {
+        let centre = self.get(row, column) as u32;
+        let columns = column.saturating_sub(1)..(column + 2).min(self.width);
+        let rows = row.saturating_sub(1)..(row + 2).min(self.height);
+
+        rows.map(|r| self.count_in_row(r, columns.clone()))
+            .sum::<u32>()
+            - centre
+    }
+
+    /// Moves every value `rows` down and `columns` right, so that each position takes the value
+    /// found at `(row - rows, column - columns)`. Positions brought in from beyond the edges
+    /// follow `edges`, wrapping around for [`Edges::Torus`] and [`Edges::KleinBottle`], or
+    /// taking the padding value, false when [`Edges::Bounded`].
+    ///
+    /// Rows are shifted a word at a time rather than a bit at a time.
+    pub fn shifted(&self, rows: isize, columns: isize, edges: Edges<bool>) -> BitGrid {
+        let mut result = BitGrid::new(self.height, self.width);
+        if self.width == 0 || self.height == 0 {
+            return result;
+        }
+
+        let (height, width) = (self.height as isize, self.width as isize);
+        for row in 0..self.height {
+            let from = row as isize - rows;
+            let (source, wraps) = match edges {
+                _ if (0..height).contains(&from) => (from as usize, false),
+                Edges::Torus => (from.rem_euclid(height) as usize, false),
+                Edges::KleinBottle => (
+                    from.rem_euclid(height) as usize,
+                    from.div_euclid(height) % 2 != 0,
+                ),
+                Edges::Bounded => continue,
+                Edges::Padded(value) => {
+                    let fill = if value { u64::MAX } else { 0 };
+                    result.row_words_mut(row).fill(fill);
+                    result.mask_row(row);
+                    continue;
+                }
+            };
+
+            // Crossing the top or bottom of a Klein bottle mirrors the row
+            let mirrored = wraps.then(|| {
+                let mut mirrored = BitGrid::new(1, self.width);
+                for column in 0..self.width {
+                    mirrored.set(0, self.width - 1 - column, self.get(source, column));
+                }
+                mirrored.words
+            });
+            let source = mirrored
+                .as_deref()
+                .unwrap_or_else(|| self.row_words(source));
+
+            let out = result.row_words_mut(row);
+            match edges {
+                Edges::Torus | Edges::KleinBottle => {
+                    let by = columns.rem_euclid(width) as usize;
+                    shift_up(source, by, out);
+                    let mut wrapped = vec![0; out.len()];
+                    shift_down(source, self.width - by, &mut wrapped);
+                    out.iter_mut().zip(wrapped).for_each(|(o, w)| *o |= w);
+                }
+                Edges::Bounded | Edges::Padded(_) => {
+                    let by = columns.unsigned_abs().min(self.width);
+                    if columns >= 0 {
+                        shift_up(source, by, out);
+                    } else {
+                        shift_down(source, by, out);
+                    }
+                    if edges == Edges::Padded(true) {
+                        let filled = if columns >= 0 {
+                            0..by
+                        } else {
+                            self.width - by..self.width
+                        };
+                        fill_range(out, filled);
+                    }
+                }
+            }
+            result.mask_row(row);
+        }
+
+        result
+    }
+
+    fn row_words_mut(&mut self, row: usize) -> &mut [u64] {
+        &mut self.words[row * self.words_per_row..(row + 1) * self.words_per_row]
+    }
+
+    fn mask_row(&mut self, row: usize) {
+        let mask = self.last_word_mask();
+        if let Some(last) = self.row_words_mut(row).last_mut() {
+            *last &= mask;
+        }
+    }
+
+    /// Combines two grids of the same size a word at a time.
+    fn combine(&self, other: &BitGrid, f: impl Fn(u64, u64) -> u64) -> Result<BitGrid, Arr2dError> {
+        if (self.height, self.width) != (other.height, other.width) {
+            return Err(Arr2dError::SizeMismatch {
+                expected: (self.height, self.width),
+                found: (other.height, other.width),
+            });
+        }
+
+        Ok(BitGrid {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            ..self.clone()
+        })
+    }
+
+    /// True wherever either grid is true.
+    pub fn union(&self, other: &BitGrid) -> Result<BitGrid, Arr2dError> {
+        self.combine(other, |a, b| a | b)
+    }
+
+    /// True wherever both grids are true.
+    pub fn intersection(&self, other: &BitGrid) -> Result<BitGrid, Arr2dError> {
+        self.combine(other, |a, b| a & b)
+    }
+
+    /// True wherever this grid is true and `other` is not.
+    pub fn difference(&self, other: &BitGrid) -> Result<BitGrid, Arr2dError> {
+        self.combine(other, |a, b| a & !b)
+    }
+
+    /// One generation of Conway's Game of Life, counting the neighbours of every position at
+    /// once. The eight shifted copies of the grid are summed with bitwise adders, 64 positions
+    /// to a word.
+    pub fn life_step(&self, edges: Edges<bool>) -> BitGrid {
+        let mut ones = vec![0u64; self.words.len()];
+        let mut twos = vec![0u64; self.words.len()];
+        let mut fours = vec![0u64; self.words.len()];
+
+        for rows in -1..=1 {
+            for columns in -1..=1 {
+                if (rows, columns) == (0, 0) {
+                    continue;
+                }
+                let neighbours = self.shifted(rows, columns, edges);
+                for (i, &word) in neighbours.words.iter().enumerate() {
+                    let carry = ones[i] & word;
+                    ones[i] ^= word;
+                    let carry_twos = twos[i] & carry;
+                    twos[i] ^= carry;
+                    // Counts of 8 wrap around to 0, which dies just the same
+                    fours[i] ^= carry_twos;
+                }
+            }
+        }
+
+        // Alive with exactly three neighbours, or two if already alive
+        let words = (0..self.words.len())
+            .map(|i| twos[i] & !fours[i] & (ones[i] | self.words[i]))
+            .collect();
+
+        BitGrid {
+            words,
+            ..self.clone()
+        }
+    }
+}
+
+/// The bits `from..to` of a word.
+fn bit_range(from: usize, to: usize) -> u64 {
+    let high = if to >= WORD_BITS {
+        u64::MAX
+    } else {
+        (1 << to) - 1
+    };
+    high & !((1u64 << from) - 1)
+}
+
+fn fill_range(words: &mut [u64], columns: Range<usize>) {
+    for column in columns {
+        words[column / WORD_BITS] |= 1 << (column % WORD_BITS);
+    }
+}
+
+/// Moves every bit of a row `by` places towards the higher columns, dropping those that fall off
+/// the end of the row.
+fn shift_up(source: &[u64], by: usize, out: &mut [u64]) {
+    let (words, bits) = (by / WORD_BITS, by % WORD_BITS);
+    for (i, out) in out.iter_mut().enumerate() {
+        *out = match i.checked_sub(words) {
+            None => 0,
+            Some(from) => {
+                let carry = match (bits, from.checked_sub(1)) {
+                    (0, _) | (_, None) => 0,
+                    (_, Some(lower)) => source[lower] >> (WORD_BITS - bits),
+                };
+                (source[from] << bits) | carry
+            }
+        };
+    }
+}
+
+/// Moves every bit of a row `by` places towards the lower columns, dropping those that fall off
+/// the start of the row.
+fn shift_down(source: &[u64], by: usize, out: &mut [u64]) {
+    let (words, bits) = (by / WORD_BITS, by % WORD_BITS);
+    for (i, out) in out.iter_mut().enumerate() {
+        let from = i + words;
+        *out = match source.get(from) {
+            None => 0,
+            Some(&word) => {
+                let carry = match (bits, source.get(from + 1)) {
+                    (0, _) | (_, None) => 0,
+                    (_, Some(&higher)) => higher << (WORD_BITS - bits),
+                };
+                (word >> bits) | carry
+            }
+        };
+    }
+}
+
+impl Grid for BitGrid {
+    type Value = bool;
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some((self.height, self.width))
+    }
+
+    fn value_at(&self, row: isize, column: isize) -> Option<bool> {
+        let (row, column) = checked_position(row, column, (self.height, self.width)).ok()?;
+        Some(self.get(row, column))
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        row: isize,
+        column: isize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (isize, isize)> + 'a {
+        bounded_neighbours(
+            row,
+            column,
+            (self.height, self.width),
+            neighbourhood.offsets(row.rem_euclid(2) as usize),
+        )
+    }
+}
+
+impl GridMut for BitGrid {
+    fn set_at(&mut self, row: isize, column: isize, value: bool) -> Result<(), Arr2dError> {
+        let (row, column) = checked_position(row, column, (self.height, self.width))?;
+        self.set(row, column, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BitGrid;
+    use crate::{Arr2d, Arr2dError, Edges, Grid, GridMut, Neighbourhood, flood_fill};
+    use test_case::test_case;
+
+    /// A scattering of true values that does not line up with word boundaries.
+    fn scattered(height: usize, width: usize) -> Arr2d<bool> {
+        Arr2d::from_fn(height, width, |row, column| {
+            (row * 7 + column * 13 + row * column).is_multiple_of(5)
+        })
+    }
+
+    const EDGES: [Edges<bool>; 5] = [
+        Edges::Bounded,
+        Edges::Torus,
+        Edges::KleinBottle,
+        Edges::Padded(true),
+        Edges::Padded(false),
+    ];
+
+    #[test]
+    fn test_get_and_set_across_words() {
+        let mut bits = BitGrid::new(3, 70);
+
+        bits.set(0, 63, true);
+        bits.set(0, 64, true);
+        bits.set(2, 69, true);
+        bits.set(0, 63, false);
+
+        assert!(!bits.get(0, 63));
+        assert!(bits.get(0, 64));
+        assert!(bits.get(2, 69));
+        assert!(!bits.get(1, 0));
+        assert_eq!(bits.count_ones(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "position (0, 70) is outside of a 3x70 grid")]
+    fn test_get_does_not_read_row_padding() {
+        let bits = BitGrid::new(3, 70);
+
+        bits.get(0, 70);
+    }
+
+    #[test]
+    fn test_arr2d_round_trip() {
+        let input = Arr2d::from_contents(vec![vec![true, false, true], vec![false, false, true]]);
+
+        let bits = BitGrid::from_arr2d(&input);
+
+        assert_eq!(bits.count_ones(), 3);
+        assert_eq!(bits.to_arr2d(), input);
+    }
+
+    #[test]
+    fn test_set_at() {
+        let mut bits = BitGrid::new(2, 2);
+
+        assert_eq!(bits.set_at(1, 1, true), Ok(()));
+        assert_eq!(
+            bits.set_at(-1, 0, true),
+            Err(Arr2dError::OutOfBounds {
+                row: -1,
+                col: 0,
+                rows: 2,
+                cols: 2
+            })
+        );
+        assert_eq!(bits.value_at(1, 1), Some(true));
+        assert_eq!(bits.dimensions(), Some((2, 2)));
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut bits = BitGrid::new(3, 100);
+        for column in 0..100 {
+            bits.set(1, column, true);
+        }
+        bits.set(0, 99, true);
+
+        let result = flood_fill(&bits, 1, 50, &Neighbourhood::VonNeumann).count();
+
+        assert_eq!(result, 101);
+    }
+
+    #[test_case(3, 5)]
+    #[test_case(4, 64)]
+    #[test_case(5, 65)]
+    #[test_case(2, 130)]
+    #[test_case(1, 1)]
+    fn test_shifted_matches_edges(height: usize, width: usize) {
+        let input = scattered(height, width);
+        let bits = BitGrid::from_arr2d(&input);
+
+        for edges in EDGES {
+            for (rows, columns) in [(0, 1), (1, 0), (-1, -1), (2, -3), (-1, 64), (0, -70)] {
+                let result = bits.shifted(rows, columns, edges).to_arr2d();
+
+                let expected = Arr2d::from_fn(height, width, |row, column| {
+                    let from = (row as isize - rows, column as isize - columns);
+                    match (edges.resolve(from.0, from.1, height, width), edges) {
+                        (Some((r, c)), _) => *input.get(r, c),
+                        (None, Edges::Padded(value)) => value,
+                        (None, _) => false,
+                    }
+                });
+                assert_eq!(
+                    result, expected,
+                    "shift ({rows}, {columns}) with {edges:?} edges"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbour_count() {
+        let input = scattered(4, 70);
+        let bits = BitGrid::from_arr2d(&input);
+
+        for cell in input.all_cells() {
+            let expected = input
+                .neighbour_values(cell.row(), cell.column(), &Neighbourhood::Moore)
+                .filter(|&v| v)
+                .count() as u32;
+
+            assert_eq!(bits.neighbour_count(cell.row(), cell.column()), expected);
+        }
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a = BitGrid::from_arr2d(&Arr2d::from_contents(vec![vec![true, true, false, false]]));
+        let b = BitGrid::from_arr2d(&Arr2d::from_contents(vec![vec![true, false, true, false]]));
+
+        let values = |grid: BitGrid| grid.to_arr2d().all_cells().map(|c| c.value()).collect();
+        let union: Vec<bool> = values(a.union(&b).unwrap());
+        let intersection: Vec<bool> = values(a.intersection(&b).unwrap());
+        let difference: Vec<bool> = values(a.difference(&b).unwrap());
+
+        assert_eq!(union, vec![true, true, true, false]);
+        assert_eq!(intersection, vec![true, false, false, false]);
+        assert_eq!(difference, vec![false, true, false, false]);
+    }
+
+    #[test]
+    fn test_set_operations_need_same_size() {
+        let result = BitGrid::new(2, 3).union(&BitGrid::new(3, 2));
+
+        assert_eq!(
+            result,
+            Err(Arr2dError::SizeMismatch {
+                expected: (2, 3),
+                found: (3, 2)
+            })
+        );
+    }
+
+    #[test_case(6, 6)]
+    #[test_case(5, 67)]
+    #[test_case(9, 130)]
+    fn test_life_step_matches_cell_by_cell(height: usize, width: usize) {
+        let input = scattered(height, width);
+        let bits = BitGrid::from_arr2d(&input);
+
+        for edges in EDGES {
+            let grid = input.clone().with_edges(edges);
+            let expected = Arr2d::from_fn(height, width, |row, column| {
+                let n = grid
+                    .neighbour_values(row, column, &Neighbourhood::Moore)
+                    .filter(|&v| v)
+                    .count();
+                n == 3 || (n == 2 && *grid.get(row, column))
+            });
+
+            assert_eq!(
+                bits.life_step(edges).to_arr2d(),
+                expected,
+                "{edges:?} edges"
+            );
+        }
+    }
+}
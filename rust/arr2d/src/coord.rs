@@ -0,0 +1,266 @@
+use crate::Neighbourhood;
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A position on a grid, or the offset between two positions. Rows grow downwards and columns
+/// to the right, and both are signed so that positions off the edge of a grid, or on a grid
+/// without bounds such as [`crate::SparseGrid`], can be represented too.
+///
+/// The identity of a [`crate::Cell`] is its position, so two cells with the same coordinates
+/// and value are equal whichever grid they came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Coord {
+    pub row: isize,
+    pub column: isize,
+}
+
+/// One of the eight compass directions, with north towards the first row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Direction {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction {
+    /// Every direction, clockwise from north.
+    pub const ALL: [Direction; 8] = [
+        Direction::North,
+        Direction::NorthEast,
+        Direction::East,
+        Direction::SouthEast,
+        Direction::South,
+        Direction::SouthWest,
+        Direction::West,
+        Direction::NorthWest,
+    ];
+
+    /// The four directions along rows and columns, clockwise from north.
+    pub const ORTHOGONAL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The change in position from a single step in this direction.
+    pub const fn offset(self) -> Coord {
+        let (row, column) = match self {
+            Direction::North => (-1, 0),
+            Direction::NorthEast => (-1, 1),
+            Direction::East => (0, 1),
+            Direction::SouthEast => (1, 1),
+            Direction::South => (1, 0),
+            Direction::SouthWest => (1, -1),
+            Direction::West => (0, -1),
+            Direction::NorthWest => (-1, -1),
+        };
+        Coord::new(row, column)
+    }
+
+    pub fn opposite(self) -> Direction {
+        self.turn(4)
+    }
+
+    /// A quarter turn clockwise.
+    pub fn turn_right(self) -> Direction {
+        self.turn(2)
+    }
+
+    /// A quarter turn anticlockwise.
+    pub fn turn_left(self) -> Direction {
+        self.turn(6)
+    }
+
+    /// Turns clockwise by the given number of eighths of a turn.
+    fn turn(self, eighths: usize) -> Direction {
+        let index = Direction::ALL.iter().position(|&d| d == self).unwrap_or(0);
+        Direction::ALL[(index + eighths) % 8]
+    }
+}
+
+impl Coord {
+    pub const ORIGIN: Coord = Coord::new(0, 0);
+
+    pub const fn new(row: isize, column: isize) -> Coord {
+        Coord { row, column }
+    }
+
+    /// The position one step away in the given direction.
+    pub fn step(self, direction: Direction) -> Coord {
+        self + direction.offset()
+    }
+
+    /// The number of steps between two positions moving only along rows and columns.
+    pub fn manhattan_distance(self, other: Coord) -> usize {
+        self.row.abs_diff(other.row) + self.column.abs_diff(other.column)
+    }
+
+    /// The number of steps between two positions when diagonal steps are allowed.
+    pub fn chebyshev_distance(self, other: Coord) -> usize {
+        self.row
+            .abs_diff(other.row)
+            .max(self.column.abs_diff(other.column))
+    }
+
+    /// The positions around this one, with no edges to stop at.
+    pub fn neighbours(self, neighbourhood: &Neighbourhood) -> impl Iterator<Item = Coord> {
+        neighbourhood
+            .offsets(self.row.rem_euclid(2) as usize)
+            .into_owned()
+            .into_iter()
+            .map(move |offset| self + Coord::from(offset))
+    }
+
+    /// The position as unsigned row and column, or `None` if either is negative.
+    pub fn to_unsigned(self) -> Option<(usize, usize)> {
+        Some((
+            usize::try_from(self.row).ok()?,
+            usize::try_from(self.column).ok()?,
+        ))
+    }
+}
+
+impl fmt::Display for Coord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.row, self.column)
+    }
+}
+
+impl From<(isize, isize)> for Coord {
+    fn from((row, column): (isize, isize)) -> Coord {
+        Coord::new(row, column)
+    }
+}
+
+impl From<Coord> for (isize, isize) {
+    fn from(coord: Coord) -> (isize, isize) {
+        (coord.row, coord.column)
+    }
+}
+
+impl Add for Coord {
+    type Output = Coord;
+
+    fn add(self, other: Coord) -> Coord {
+        Coord::new(self.row + other.row, self.column + other.column)
+    }
+}
+
+impl Sub for Coord {
+    type Output = Coord;
+
+    fn sub(self, other: Coord) -> Coord {
+        Coord::new(self.row - other.row, self.column - other.column)
+    }
+}
+
+impl Neg for Coord {
+    type Output = Coord;
+
+    fn neg(self) -> Coord {
+        Coord::new(-self.row, -self.column)
+    }
+}
+
+impl Mul<isize> for Coord {
+    type Output = Coord;
+
+    fn mul(self, factor: isize) -> Coord {
+        Coord::new(self.row * factor, self.column * factor)
+    }
+}
+
+impl AddAssign for Coord {
+    fn add_assign(&mut self, other: Coord) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Coord {
+    fn sub_assign(&mut self, other: Coord) {
+        *self = *self - other;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Coord, Direction};
+    use crate::Neighbourhood;
+    use test_case::test_case;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Coord::new(2, -3);
+        let b = Coord::new(-1, 5);
+
+        assert_eq!(a + b, Coord::new(1, 2));
+        assert_eq!(a - b, Coord::new(3, -8));
+        assert_eq!(-a, Coord::new(-2, 3));
+        assert_eq!(b * 3, Coord::new(-3, 15));
+
+        let mut c = a;
+        c += b;
+        c -= a;
+        assert_eq!(c, b);
+    }
+
+    #[test_case(Direction::North, Coord::new(-1, 0))]
+    #[test_case(Direction::SouthEast, Coord::new(1, 1))]
+    #[test_case(Direction::West, Coord::new(0, -1))]
+    fn test_step(direction: Direction, expected: Coord) {
+        assert_eq!(Coord::ORIGIN.step(direction), expected);
+        assert_eq!(expected.step(direction.opposite()), Coord::ORIGIN);
+    }
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::SouthWest.opposite(), Direction::NorthEast);
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_right().turn_left(), direction);
+            assert_eq!(
+                direction.offset() + direction.opposite().offset(),
+                Coord::ORIGIN
+            );
+        }
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Coord::new(1, 1);
+        let b = Coord::new(-2, 5);
+
+        assert_eq!(a.manhattan_distance(b), 7);
+        assert_eq!(a.chebyshev_distance(b), 4);
+    }
+
+    #[test]
+    fn test_neighbours_match_directions() {
+        let mut result: Vec<Coord> = Coord::new(4, 4)
+            .neighbours(&Neighbourhood::VonNeumann)
+            .collect();
+        let mut expected: Vec<Coord> = Direction::ORTHOGONAL
+            .iter()
+            .map(|&d| Coord::new(4, 4).step(d))
+            .collect();
+        result.sort();
+        expected.sort();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_to_unsigned() {
+        assert_eq!(Coord::new(2, 3).to_unsigned(), Some((2, 3)));
+        assert_eq!(Coord::new(-1, 3).to_unsigned(), None);
+    }
+}
@@ -0,0 +1,245 @@
+use crate::{Arr2d, Arr2dError, CellValue};
+
+/// A position whose value differs between two grids of the same size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Change<T> {
+    pub row: usize,
+    pub column: usize,
+    pub old: T,
+    pub new: T,
+}
+
+impl<T> Change<T>
+where
+    T: CellValue,
+{
+    /// The change that undoes this one.
+    pub fn reversed(&self) -> Change<T> {
+        Change {
+            old: self.new,
+            new: self.old,
+            ..*self
+        }
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue,
+{
+    pub(crate) fn check_same_size<U: CellValue>(&self, other: &Arr2d<U>) -> Result<(), Arr2dError> {
+        if (self.height, self.width) == (other.height, other.width) {
+            Ok(())
+        } else {
+            Err(Arr2dError::SizeMismatch {
+                expected: (self.height, self.width),
+                found: (other.height, other.width),
+            })
+        }
+    }
+
+    /// The positions where `other` holds a different value, in row-major order. Applying them to
+    /// this grid with [`Arr2d::apply_patch`] gives `other`.
+    pub fn diff(&self, other: &Arr2d<T>) -> Result<Vec<Change<T>>, Arr2dError> {
+        self.check_same_size(other)?;
+
+        Ok(self
+            .all_cells()
+            .zip(&other.contents)
+            .filter(|(cell, new)| cell.value != **new)
+            .map(|(cell, &new)| Change {
+                row: cell.row,
+                column: cell.column,
+                old: cell.value,
+                new,
+            })
+            .collect())
+    }
+
+    /// Replays the changes from a [`Arr2d::diff`]. Nothing is changed unless every change is on
+    /// the grid and finds the old value it expects, so a patch cannot be half applied.
+    pub fn apply_patch(&mut self, changes: &[Change<T>]) -> Result<(), Arr2dError> {
+        for change in changes {
+            if *self.try_get(change.row, change.column)? != change.old {
+                return Err(Arr2dError::PatchConflict {
+                    row: change.row,
+                    col: change.column,
+                });
+            }
+        }
+
+        for change in changes {
+            self.set(change.row, change.column, change.new);
+        }
+        Ok(())
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue + Into<char>,
+{
+    /// Shows this grid and `other` side by side, followed by a third panel holding only the new
+    /// values of positions that changed. Rows with changes are marked with `>`.
+    ///
+    /// ```text
+    ///   old | new | changes
+    ///   ab  | ab  | ..
+    /// > cd  | xd  | x.
+    /// ```
+    pub fn render_diff(&self, other: &Arr2d<T>) -> Result<String, Arr2dError> {
+        self.check_same_size(other)?;
+
+        let labels = ["old", "new", "changes"];
+        let width = self.width.max(labels[0].len()).max(labels[1].len());
+        let mut as_str = format!(
+            "  {:<width$} | {:<width$} | {}\n",
+            labels[0], labels[1], labels[2]
+        );
+
+        for (old, new) in self.rows_iter().zip(other.rows_iter()) {
+            let old_row: String = old.iter().map(|&v| v.into()).collect();
+            let new_row: String = new.iter().map(|&v| v.into()).collect();
+            let changes: String = old
+                .iter()
+                .zip(new)
+                .map(|(o, &n)| if *o == n { '.' } else { n.into() })
+                .collect();
+            let marker = if old == new { ' ' } else { '>' };
+            as_str.push_str(&format!(
+                "{marker} {old_row:<width$} | {new_row:<width$} | {changes}\n"
+            ));
+        }
+
+        Ok(as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Change;
+    use crate::Arr2dError;
+    use crate::test_helpers::grid;
+
+    #[test]
+    fn test_diff() {
+        let before = grid("abc\ndef");
+        let after = grid("abx\nyef");
+
+        let result = before.diff(&after).unwrap();
+
+        assert_eq!(
+            result,
+            vec![
+                Change {
+                    row: 0,
+                    column: 2,
+                    old: 'c',
+                    new: 'x'
+                },
+                Change {
+                    row: 1,
+                    column: 0,
+                    old: 'd',
+                    new: 'y'
+                },
+            ]
+        );
+        assert_eq!(before.diff(&before), Ok(vec![]));
+    }
+
+    #[test]
+    fn test_diff_size_mismatch() {
+        let result = grid("ab\ncd").diff(&grid("abc"));
+
+        assert_eq!(
+            result,
+            Err(Arr2dError::SizeMismatch {
+                expected: (2, 2),
+                found: (1, 3)
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let before = grid("abc\ndef");
+        let after = grid("xbc\ndez");
+        let changes = before.diff(&after).unwrap();
+
+        let mut result = before.clone();
+        result.apply_patch(&changes).unwrap();
+        assert_eq!(result, after);
+
+        let reversed: Vec<Change<char>> = changes.iter().rev().map(|c| c.reversed()).collect();
+        result.apply_patch(&reversed).unwrap();
+        assert_eq!(result, before);
+    }
+
+    #[test]
+    fn test_apply_patch_conflict_changes_nothing() {
+        let mut input = grid("abc\ndef");
+        let changes = [
+            Change {
+                row: 0,
+                column: 0,
+                old: 'a',
+                new: 'x',
+            },
+            Change {
+                row: 1,
+                column: 1,
+                old: 'q',
+                new: 'y',
+            },
+        ];
+
+        let result = input.apply_patch(&changes);
+
+        assert_eq!(result, Err(Arr2dError::PatchConflict { row: 1, col: 1 }));
+        assert_eq!(input, grid("abc\ndef"));
+    }
+
+    #[test]
+    fn test_apply_patch_off_grid() {
+        let mut input = grid("ab");
+        let changes = [Change {
+            row: 3,
+            column: 0,
+            old: 'a',
+            new: 'b',
+        }];
+
+        assert!(matches!(
+            input.apply_patch(&changes),
+            Err(Arr2dError::OutOfBounds { .. })
+        ));
+    }
+
+    #[test]
+    fn test_render_diff() {
+        let before = grid("-W-W-\nWBW--\nWBBW-");
+        let after = grid("-W-W-\nW-W--\nW--W-");
+
+        let result = before.render_diff(&after).unwrap();
+
+        assert_eq!(
+            result,
+            "  old   | new   | changes\n  \
+               -W-W- | -W-W- | .....\n\
+             > WBW-- | W-W-- | .-...\n\
+             > WBBW- | W--W- | .--..\n"
+        );
+    }
+
+    #[test]
+    fn test_render_diff_narrow_grid() {
+        let result = grid("a\nb").render_diff(&grid("a\nc")).unwrap();
+
+        assert_eq!(
+            result,
+            "  old | new | changes\n  a   | a   | .\n> b   | c   | c\n"
+        );
+    }
+}
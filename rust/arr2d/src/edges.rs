@@ -0,0 +1,108 @@
+/// What lies beyond the edges of a grid when looking up neighbours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Edges<T> {
+    /// Nothing, positions off the grid have no neighbours.
+    #[default]
+    Bounded,
+    /// Opposite edges are joined, so leaving the right edge re-enters on the left and leaving the
+    /// bottom re-enters at the top.
+    Torus,
+    /// Left and right edges are joined as on a torus, but crossing the top or bottom edge also
+    /// mirrors the column. Transforms that swap rows and columns, such as
+    /// [`Arr2d::rotate_90`](crate::Arr2d::rotate_90), turn these edges into [`Edges::Bounded`].
+    KleinBottle,
+    /// Positions off the grid behave as if they held a fixed value. They have no place in the grid
+    /// so they only show up in neighbour values, never as cells.
+    Padded(T),
+}
+
+impl<T> Edges<T> {
+    /// Converts the padding value, leaving other edge policies as they are.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Edges<U> {
+        match self {
+            Edges::Bounded => Edges::Bounded,
+            Edges::Torus => Edges::Torus,
+            Edges::KleinBottle => Edges::KleinBottle,
+            Edges::Padded(value) => Edges::Padded(f(value)),
+        }
+    }
+
+    /// The same shape of edges for a grid holding another type, such as labels or distances
+    /// derived from this one. There is nothing to pad those grids with, so padding is dropped.
+    pub(crate) fn without_padding<U>(&self) -> Edges<U> {
+        match self {
+            Edges::Torus => Edges::Torus,
+            Edges::KleinBottle => Edges::KleinBottle,
+            Edges::Bounded | Edges::Padded(_) => Edges::Bounded,
+        }
+    }
+
+    /// Maps a possibly off-grid position onto the grid, or `None` if it has no place on it.
+    pub fn resolve(
+        &self,
+        row: isize,
+        column: isize,
+        height: usize,
+        width: usize,
+    ) -> Option<(usize, usize)> {
+        let (height, width) = (height as isize, width as isize);
+        if height == 0 || width == 0 {
+            return None;
+        }
+
+        match self {
+            Edges::Bounded | Edges::Padded(_) => {
+                if (0..height).contains(&row) && (0..width).contains(&column) {
+                    Some((row as usize, column as usize))
+                } else {
+                    None
+                }
+            }
+            Edges::Torus => Some((
+                row.rem_euclid(height) as usize,
+                column.rem_euclid(width) as usize,
+            )),
+            Edges::KleinBottle => {
+                let column = column.rem_euclid(width);
+                let column = if row.div_euclid(height) % 2 == 0 {
+                    column
+                } else {
+                    width - 1 - column
+                };
+                Some((row.rem_euclid(height) as usize, column as usize))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Edges;
+    use test_case::test_case;
+
+    #[test_case(Edges::Bounded, (1, 2), Some((1, 2)))]
+    #[test_case(Edges::Bounded, (-1, 2), None)]
+    #[test_case(Edges::Bounded, (1, 5), None)]
+    #[test_case(Edges::Padded(true), (3, 0), None)]
+    #[test_case(Edges::Torus, (-1, 2), Some((2, 2)))]
+    #[test_case(Edges::Torus, (3, 5), Some((0, 0)))]
+    #[test_case(Edges::Torus, (1, -1), Some((1, 4)))]
+    #[test_case(Edges::Torus, (-4, -6), Some((2, 4)))]
+    #[test_case(Edges::KleinBottle, (1, -1), Some((1, 4)))]
+    #[test_case(Edges::KleinBottle, (-1, 0), Some((2, 4)))]
+    #[test_case(Edges::KleinBottle, (3, 1), Some((0, 3)))]
+    #[test_case(Edges::KleinBottle, (6, 1), Some((0, 1)))]
+    fn test_resolve(
+        edges: Edges<bool>,
+        (row, column): (isize, isize),
+        expected: Option<(usize, usize)>,
+    ) {
+        assert_eq!(edges.resolve(row, column, 3, 5), expected);
+    }
+
+    #[test]
+    fn test_resolve_empty_grid() {
+        assert_eq!(Edges::<bool>::Torus.resolve(0, 0, 0, 0), None);
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{Arr2d, Arr2dError, CellValue, Coord, Edges, Neighbourhood, View};
+use std::borrow::Cow;
+use std::collections::HashSet;
+
+/// Read access to a grid of values, whatever its storage. Positions are signed so that grids
+/// without fixed bounds, such as [`crate::SparseGrid`], share the same interface. Positions a grid
+/// has no place for have no value.
+///
+/// Algorithms written against this trait, such as [`flood_fill`] and [`perimeter`], work the
+/// same on an [`Arr2d`], a [`View`] of one, a [`crate::BitGrid`] or a [`crate::SparseGrid`].
+pub trait Grid {
+    type Value: CellValue;
+
+    /// The number of rows and columns, or `None` for grids without bounds.
+    fn dimensions(&self) -> Option<(usize, usize)>;
+
+    fn value_at(&self, row: isize, column: isize) -> Option<Self::Value>;
+
+    /// The positions next to the given one, following whatever edge policy the grid has.
+    fn neighbours<'a>(
+        &'a self,
+        row: isize,
+        column: isize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (isize, isize)> + 'a;
+}
+
+/// Write access to a grid of values.
+pub trait GridMut: Grid {
+    /// Stores a value, failing if the grid has no place for the position.
+    fn set_at(&mut self, row: isize, column: isize, value: Self::Value) -> Result<(), Arr2dError>;
+}
+
+/// Neighbours for grids that stop at their edges, for a grid of the given size.
+pub(crate) fn bounded_neighbours<'a>(
+    row: isize,
+    column: isize,
+    (height, width): (usize, usize),
+    offsets: Cow<'a, [(isize, isize)]>,
+) -> impl Iterator<Item = (isize, isize)> + 'a {
+    let on_grid = (0..height as isize).contains(&row) && (0..width as isize).contains(&column);
+    let count = if on_grid { offsets.len() } else { 0 };
+    (0..count).filter_map(move |i| {
+        let (dr, dc) = offsets[i];
+        Edges::<()>::Bounded
+            .resolve(row + dr, column + dc, height, width)
+            .map(|(r, c)| (r as isize, c as isize))
+    })
+}
+
+/// Converts a signed position to an index into a grid of the given size.
+pub(crate) fn checked_position(
+    row: isize,
+    column: isize,
+    (height, width): (usize, usize),
+) -> Result<(usize, usize), Arr2dError> {
+    match (usize::try_from(row), usize::try_from(column)) {
+        (Ok(r), Ok(c)) if r < height && c < width => Ok((r, c)),
+        _ => Err(Arr2dError::OutOfBounds {
+            row,
+            col: column,
+            rows: height,
+            cols: width,
+        }),
+    }
+}
+
+impl<T> Grid for Arr2d<T>
+where
+    T: CellValue,
+{
+    type Value = T;
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some((self.height, self.width))
+    }
+
+    fn value_at(&self, row: isize, column: isize) -> Option<T> {
+        let (row, column) = checked_position(row, column, (self.height, self.width)).ok()?;
+        Some(self.contents[self.index_of(row, column)])
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        row: isize,
+        column: isize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (isize, isize)> + 'a {
+        let on_grid = self.value_at(row, column).is_some();
+        on_grid
+            .then(|| {
+                self.get_neighbours(row as usize, column as usize, neighbourhood)
+                    .map(|c| (c.row as isize, c.column as isize))
+            })
+            .into_iter()
+            .flatten()
+    }
+}
+
+impl<T> GridMut for Arr2d<T>
+where
+    T: CellValue,
+{
+    fn set_at(&mut self, row: isize, column: isize, value: T) -> Result<(), Arr2dError> {
+        let (row, column) = checked_position(row, column, (self.height, self.width))?;
+        self.set(row, column, value);
+        Ok(())
+    }
+}
+
+/// Positions are relative to the view, and neighbours stop at the edges of the view whatever the
+/// edges of the underlying grid.
+impl<T> Grid for View<'_, T>
+where
+    T: CellValue,
+{
+    type Value = T;
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        Some((self.height(), self.width()))
+    }
+
+    fn value_at(&self, row: isize, column: isize) -> Option<T> {
+        let (row, column) = checked_position(row, column, (self.height(), self.width())).ok()?;
+        Some(*self.get(row, column))
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        row: isize,
+        column: isize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (isize, isize)> + 'a {
+        // Hex layouts depend on the row within the whole grid
+        let grid_row = (self.rect().row as isize + row).rem_euclid(2) as usize;
+        bounded_neighbours(
+            row,
+            column,
+            (self.height(), self.width()),
+            neighbourhood.offsets(grid_row),
+        )
+    }
+}
+
+/// Whether `from` is among the neighbours of `to`, so that the link from `from` to `to` goes both
+/// ways. Always true for a symmetric neighbourhood.
+pub(crate) fn links_back<G: Grid>(
+    grid: &G,
+    from: (isize, isize),
+    to: (isize, isize),
+    neighbourhood: &Neighbourhood,
+) -> bool {
+    grid.neighbours(to.0, to.1, neighbourhood)
+        .any(|position| position == from)
+}
+
+/// The positions connected to a starting one through neighbours holding the same value, the
+/// starting position included. Nothing is found if the start has no value. Two positions are
+/// only connected when each is a neighbour of the other, which matters for a
+/// [`Neighbourhood::Custom`] list that has an offset without its opposite.
+///
+/// The fill is lazy, which matters for grids without bounds: filling the background of a
+/// [`crate::SparseGrid`] never ends, so only take as many positions as needed.
+pub fn flood_fill<'a, G: Grid>(
+    grid: &'a G,
+    row: isize,
+    column: isize,
+    neighbourhood: &'a Neighbourhood,
+) -> impl Iterator<Item = (isize, isize)> + 'a {
+    let start_value = grid.value_at(row, column);
+    let start = Coord::new(row, column);
+    let symmetric = neighbourhood.is_symmetric();
+    let mut to_visit: Vec<Coord> = Vec::new();
+    let mut seen: HashSet<Coord> = HashSet::new();
+    if start_value.is_some() {
+        to_visit.push(start);
+        seen.insert(start);
+    }
+
+    std::iter::from_fn(move || {
+        let position = to_visit.pop()?;
+        for neighbour in grid.neighbours(position.row, position.column, neighbourhood) {
+            let neighbour = Coord::from(neighbour);
+            if grid.value_at(neighbour.row, neighbour.column) == start_value
+                && (symmetric || links_back(grid, position.into(), neighbour.into(), neighbourhood))
+                && seen.insert(neighbour)
+            {
+                to_visit.push(neighbour);
+            }
+        }
+
+        Some(position.into())
+    })
+}
+
+/// The positions next to the region found by [`flood_fill`] that hold a different value, each
+/// given once.
+pub fn perimeter<'a, G: Grid>(
+    grid: &'a G,
+    row: isize,
+    column: isize,
+    neighbourhood: &'a Neighbourhood,
+) -> impl Iterator<Item = (isize, isize)> + 'a {
+    let start_value = grid.value_at(row, column);
+    let mut seen: HashSet<Coord> = HashSet::new();
+
+    flood_fill(grid, row, column, neighbourhood)
+        .flat_map(move |(r, c)| grid.neighbours(r, c, neighbourhood))
+        .filter(move |&(r, c)| grid.value_at(r, c) != start_value)
+        .filter(move |&position| seen.insert(position.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Grid, GridMut, flood_fill, perimeter};
+    use crate::test_helpers::grid;
+    use crate::{Arr2dError, BitGrid, Edges, Neighbourhood, Rect};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_arr2d_value_at() {
+        let input = grid("ab\ncd");
+
+        assert_eq!(input.value_at(1, 0), Some('c'));
+        assert_eq!(input.value_at(-1, 0), None);
+        assert_eq!(input.value_at(0, 2), None);
+    }
+
+    #[test]
+    fn test_arr2d_neighbours_follow_edges() {
+        let input = grid("abc\ndef\nghi").with_edges(Edges::Torus);
+
+        let result: Vec<(isize, isize)> =
+            input.neighbours(0, 0, &Neighbourhood::VonNeumann).collect();
+
+        assert_eq!(result, vec![(0, 2), (0, 1), (2, 0), (1, 0)]);
+        assert_eq!(
+            input.neighbours(3, 0, &Neighbourhood::VonNeumann).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_generic_flood_fill_matches_arr2d() {
+        let input = grid("aab-\nbabb\nbaab");
+
+        let result: HashSet<(isize, isize)> =
+            flood_fill(&input, 1, 1, &Neighbourhood::VonNeumann).collect();
+        let expected: HashSet<(isize, isize)> = input
+            .flood_fill(1, 1, &Neighbourhood::VonNeumann)
+            .unwrap()
+            .map(|c| (c.row() as isize, c.column() as isize))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_generic_perimeter_matches_arr2d() {
+        let input = grid("aab-\nbabb\nbaab");
+
+        let result: HashSet<(isize, isize)> =
+            perimeter(&input, 1, 1, &Neighbourhood::VonNeumann).collect();
+        let expected: HashSet<(isize, isize)> = input
+            .get_perimeter(1, 1, &Neighbourhood::VonNeumann)
+            .unwrap()
+            .map(|c| (c.row() as isize, c.column() as isize))
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_flood_fill_off_grid() {
+        let input = grid("ab\ncd");
+
+        assert_eq!(flood_fill(&input, 5, 5, &Neighbourhood::Moore).count(), 0);
+    }
+
+    #[test]
+    fn test_flood_fill_skips_one_way_links() {
+        let input = grid("aaa\naaa");
+        let neighbourhood = Neighbourhood::Custom(vec![(0, 1), (0, -1), (1, 0)]);
+
+        let result: HashSet<(isize, isize)> = flood_fill(&input, 0, 0, &neighbourhood).collect();
+
+        assert_eq!(result, HashSet::from([(0, 0), (0, 1), (0, 2)]));
+    }
+
+    #[test]
+    fn test_arr2d_set_at() {
+        let mut input = grid("ab\ncd");
+
+        assert_eq!(input.set_at(1, 1, 'x'), Ok(()));
+        assert_eq!(
+            input.set_at(0, -1, 'x'),
+            Err(Arr2dError::OutOfBounds {
+                row: 0,
+                col: -1,
+                rows: 2,
+                cols: 2
+            })
+        );
+        assert_eq!(input, grid("ab\ncx"));
+    }
+
+    #[test]
+    fn test_view_stops_at_its_edges() {
+        let input = grid("xxxx\nxxxx\nxxxx").with_edges(Edges::Torus);
+        let view = input.view(Rect::new(1, 1, 2, 2)).unwrap();
+
+        assert_eq!(view.dimensions(), Some((2, 2)));
+        assert_eq!(view.value_at(0, 0), Some('x'));
+        assert_eq!(view.value_at(2, 0), None);
+        assert_eq!(flood_fill(&view, 0, 0, &Neighbourhood::Moore).count(), 4);
+        assert_eq!(
+            view.neighbours(0, 0, &Neighbourhood::VonNeumann)
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (1, 0)]
+        );
+    }
+
+    /// Counts the region around a position without knowing how the grid is stored.
+    fn region_size<G: Grid>(grid: &G, row: isize, column: isize) -> usize {
+        flood_fill(grid, row, column, &Neighbourhood::VonNeumann).count()
+    }
+
+    #[test]
+    fn test_algorithms_are_generic_over_storage() {
+        let input = grid("x-xx\nxx-x\n--xx").map(|v| v == 'x');
+        let bits = BitGrid::from_arr2d(&input);
+        let view = input.view(Rect::new(0, 2, 3, 2)).unwrap();
+
+        assert_eq!(region_size(&input, 0, 2), 5);
+        assert_eq!(region_size(&bits, 0, 2), 5);
+        assert_eq!(region_size(&view, 0, 0), 5);
+        assert_eq!(region_size(&input, 0, 0), 3);
+        assert_eq!(region_size(&bits, 0, 0), 3);
+    }
+}
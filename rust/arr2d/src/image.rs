@@ -0,0 +1,851 @@
+use crate::{Arr2d, CellValue, Edges};
+use std::fmt;
+
+/// An RGB colour, eight bits per channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Colour {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Colour {
+    pub const BLACK: Colour = Colour::grey(0);
+    pub const WHITE: Colour = Colour::grey(255);
+
+    pub const fn new(red: u8, green: u8, blue: u8) -> Colour {
+        Colour { red, green, blue }
+    }
+
+    pub const fn grey(level: u8) -> Colour {
+        Colour::new(level, level, level)
+    }
+
+    /// The perceived brightness of the colour, used when writing greyscale images.
+    pub fn luminance(&self) -> u8 {
+        let weighted = 299 * self.red as u32 + 587 * self.green as u32 + 114 * self.blue as u32;
+        (weighted / 1000) as u8
+    }
+
+    fn distance(&self, other: &Colour) -> u32 {
+        [
+            self.red.abs_diff(other.red),
+            self.green.abs_diff(other.green),
+            self.blue.abs_diff(other.blue),
+        ]
+        .iter()
+        .map(|&d| d as u32 * d as u32)
+        .sum()
+    }
+}
+
+/// Converts cell values to and from the colours used for them in an [`ImageFormat`].
+pub trait ColourMap<T> {
+    fn encode(&self, value: T) -> Colour;
+
+    /// The value for a colour read from an image, or `None` if the colour has no meaning.
+    fn decode(&self, colour: Colour) -> Option<T>;
+}
+
+/// `true` is black and `false` is white, following the PBM convention. Colours darker than mid
+/// grey read as `true`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlackAndWhite;
+
+impl ColourMap<bool> for BlackAndWhite {
+    fn encode(&self, value: bool) -> Colour {
+        if value { Colour::BLACK } else { Colour::WHITE }
+    }
+
+    fn decode(&self, colour: Colour) -> Option<bool> {
+        Some(colour.luminance() < 128)
+    }
+}
+
+/// Each value is its own grey level, from 0 for black to 255 for white.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Greyscale;
+
+impl ColourMap<u8> for Greyscale {
+    fn encode(&self, value: u8) -> Colour {
+        Colour::grey(value)
+    }
+
+    fn decode(&self, colour: Colour) -> Option<u8> {
+        Some(colour.luminance())
+    }
+}
+
+/// A fixed table of values and the colours that stand for them. Colours read from an image
+/// take the value of the nearest colour in the table, so images touched up in an editor still
+/// load.
+#[derive(Debug, Clone)]
+pub struct Palette<T> {
+    colours: Vec<(T, Colour)>,
+}
+
+impl<T> Palette<T> {
+    pub fn new(colours: impl IntoIterator<Item = (T, Colour)>) -> Palette<T> {
+        Palette {
+            colours: colours.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> ColourMap<T> for Palette<T>
+where
+    T: PartialEq + Copy,
+{
+    /// # Panics
+    ///
+    /// Panics if the value has no colour.
+    fn encode(&self, value: T) -> Colour {
+        match self.colours.iter().find(|(v, _)| *v == value) {
+            Some(&(_, colour)) => colour,
+            None => panic!("Palette has no colour for value"),
+        }
+    }
+
+    fn decode(&self, colour: Colour) -> Option<T> {
+        self.colours
+            .iter()
+            .min_by_key(|(_, c)| c.distance(&colour))
+            .map(|&(value, _)| value)
+    }
+}
+
+/// How the pixels of a PBM or PGM image are stored: as ASCII numbers, or packed bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    Plain,
+    #[default]
+    Raw,
+}
+
+/// Errors raised while reading images into grids.
+#[derive(Debug, PartialEq)]
+pub enum ImageError {
+    UnknownFormat {
+        found: String,
+    },
+    InvalidHeader {
+        found: String,
+    },
+    MissingPixels {
+        expected: usize,
+        found: usize,
+    },
+    InvalidSample {
+        row: usize,
+        column: usize,
+        found: u32,
+        max: u32,
+    },
+    UnmappedColour {
+        row: usize,
+        column: usize,
+        colour: Colour,
+    },
+    UnevenScale {
+        height: usize,
+        width: usize,
+        scale: usize,
+    },
+    TooLarge {
+        height: usize,
+        width: usize,
+        scale: usize,
+    },
+    #[cfg(feature = "png")]
+    Png(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ImageError::UnknownFormat { found } => {
+                write!(
+                    f,
+                    "unknown image format {found:?}, expected P1, P2, P4 or P5"
+                )
+            }
+            ImageError::InvalidHeader { found } => {
+                write!(f, "invalid image header value {found:?}")
+            }
+            ImageError::MissingPixels { expected, found } => {
+                write!(f, "expected {expected} pixels, found {found}")
+            }
+            ImageError::InvalidSample {
+                row,
+                column,
+                found,
+                max,
+            } => write!(
+                f,
+                "pixel ({row}, {column}): sample {found} is above the maximum of {max}"
+            ),
+            ImageError::UnmappedColour {
+                row,
+                column,
+                colour,
+            } => write!(
+                f,
+                "pixel ({row}, {column}): no value for colour ({}, {}, {})",
+                colour.red, colour.green, colour.blue
+            ),
+            ImageError::UnevenScale {
+                height,
+                width,
+                scale,
+            } => write!(
+                f,
+                "a {height}x{width} image does not divide into squares {scale} pixels across"
+            ),
+            ImageError::TooLarge {
+                height,
+                width,
+                scale,
+            } => write!(
+                f,
+                "a {height}x{width} grid is too large to draw with squares {scale} pixels across"
+            ),
+            #[cfg(feature = "png")]
+            ImageError::Png(message) => write!(f, "PNG encoding failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+/// An image layout for grids, with cells coloured by a [`ColourMap`] and drawn as squares of
+/// `scale` pixels. Grids can be written as PBM (black and white), PGM (greyscale) or, with the
+/// `png` feature, colour PNG images.
+///
+/// Both PBM and PGM images can be read back, whichever encoding they use. Reading samples the
+/// top left pixel of each square, so images written with a scale should be read with the same
+/// one. Reading an image whose sides are not a multiple of the scale fails.
+#[derive(Debug, Clone)]
+pub struct ImageFormat<M> {
+    map: M,
+    scale: usize,
+    encoding: Encoding,
+}
+
+impl Default for ImageFormat<BlackAndWhite> {
+    fn default() -> Self {
+        ImageFormat::new(BlackAndWhite)
+    }
+}
+
+/// Plain images keep their lines to this length, as the format asks.
+const PLAIN_LINE_LENGTH: usize = 70;
+
+struct Header {
+    magic: u8,
+    width: usize,
+    height: usize,
+    max: u32,
+}
+
+impl<M> ImageFormat<M> {
+    pub fn new(map: M) -> ImageFormat<M> {
+        ImageFormat {
+            map,
+            scale: 1,
+            encoding: Encoding::default(),
+        }
+    }
+
+    /// Draws each cell as a square this many pixels across.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the scale is 0.
+    pub fn with_scale(mut self, scale: usize) -> ImageFormat<M> {
+        assert!(scale > 0, "ImageFormat scale must be at least 1");
+        self.scale = scale;
+        self
+    }
+
+    pub fn with_encoding(mut self, encoding: Encoding) -> ImageFormat<M> {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Writes a black and white image, where colours darker than mid grey are black.
+    pub fn to_pbm<T>(&self, grid: &Arr2d<T>) -> Result<Vec<u8>, ImageError>
+    where
+        T: CellValue,
+        M: ColourMap<T>,
+    {
+        let (height, width, pixels) = self.pixels(grid)?;
+        let black: Vec<bool> = pixels.iter().map(|c| c.luminance() < 128).collect();
+
+        match self.encoding {
+            Encoding::Plain => {
+                let mut image = format!("P1\n{width} {height}\n");
+                for row in black.chunks(width.max(1)) {
+                    for line in row.chunks(PLAIN_LINE_LENGTH) {
+                        image.extend(line.iter().map(|&b| if b { '1' } else { '0' }));
+                        image.push('\n');
+                    }
+                }
+                Ok(image.into_bytes())
+            }
+            Encoding::Raw => {
+                let mut image = format!("P4\n{width} {height}\n").into_bytes();
+                for row in black.chunks(width.max(1)) {
+                    for byte in row.chunks(8) {
+                        let packed = byte
+                            .iter()
+                            .enumerate()
+                            .fold(0u8, |acc, (i, &b)| acc | (u8::from(b) << (7 - i)));
+                        image.push(packed);
+                    }
+                }
+                Ok(image)
+            }
+        }
+    }
+
+    /// Writes a greyscale image using the luminance of each colour.
+    pub fn to_pgm<T>(&self, grid: &Arr2d<T>) -> Result<Vec<u8>, ImageError>
+    where
+        T: CellValue,
+        M: ColourMap<T>,
+    {
+        let (height, width, pixels) = self.pixels(grid)?;
+        let levels = pixels.iter().map(|c| c.luminance());
+
+        match self.encoding {
+            Encoding::Plain => {
+                let mut image = format!("P2\n{width} {height}\n255\n");
+                let mut line_length = 0;
+                for level in levels {
+                    let sample = level.to_string();
+                    if line_length + sample.len() + 1 > PLAIN_LINE_LENGTH {
+                        image.push('\n');
+                        line_length = 0;
+                    } else if line_length > 0 {
+                        image.push(' ');
+                        line_length += 1;
+                    }
+                    image.push_str(&sample);
+                    line_length += sample.len();
+                }
+                image.push('\n');
+                Ok(image.into_bytes())
+            }
+            Encoding::Raw => {
+                let mut image = format!("P5\n{width} {height}\n255\n").into_bytes();
+                image.extend(levels);
+                Ok(image)
+            }
+        }
+    }
+
+    /// Writes an 8-bit RGB PNG image.
+    #[cfg(feature = "png")]
+    pub fn to_png<T>(&self, grid: &Arr2d<T>) -> Result<Vec<u8>, ImageError>
+    where
+        T: CellValue,
+        M: ColourMap<T>,
+    {
+        let (height, width, pixels) = self.pixels(grid)?;
+        let data: Vec<u8> = pixels
+            .iter()
+            .flat_map(|c| [c.red, c.green, c.blue])
+            .collect();
+        let to_error = |e: png::EncodingError| ImageError::Png(e.to_string());
+
+        let mut image = Vec::new();
+        let mut encoder = png::Encoder::new(&mut image, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(to_error)?;
+        writer.write_image_data(&data).map_err(to_error)?;
+        writer.finish().map_err(to_error)?;
+
+        Ok(image)
+    }
+
+    /// Reads a PBM or PGM image, plain or raw. PBM pixels are read as black or white, and PGM
+    /// samples are scaled to grey levels from 0 to 255.
+    pub fn parse<T>(&self, bytes: &[u8]) -> Result<Arr2d<T>, ImageError>
+    where
+        T: CellValue,
+        M: ColourMap<T>,
+    {
+        let (header, raster) = parse_header(bytes)?;
+        if !header.height.is_multiple_of(self.scale) || !header.width.is_multiple_of(self.scale) {
+            return Err(ImageError::UnevenScale {
+                height: header.height,
+                width: header.width,
+                scale: self.scale,
+            });
+        }
+        let levels = read_levels(&header, &bytes[raster..])?;
+
+        let height = header.height / self.scale;
+        let width = header.width / self.scale;
+        let mut contents = Vec::with_capacity(height * width);
+        for row in 0..height {
+            for column in 0..width {
+                let level = levels[row * self.scale * header.width + column * self.scale];
+                let colour = Colour::grey(level);
+                let value = self.map.decode(colour).ok_or(ImageError::UnmappedColour {
+                    row,
+                    column,
+                    colour,
+                })?;
+                contents.push(value);
+            }
+        }
+
+        Ok(Arr2d {
+            width,
+            height,
+            contents,
+            edges: Edges::Bounded,
+        })
+    }
+
+    /// The colour of every pixel in row-major order, with the height and width of the image.
+    /// Fails if the number of pixels would not fit in a `usize`.
+    fn pixels<T>(&self, grid: &Arr2d<T>) -> Result<(usize, usize, Vec<Colour>), ImageError>
+    where
+        T: CellValue,
+        M: ColourMap<T>,
+    {
+        let too_large = || ImageError::TooLarge {
+            height: grid.height,
+            width: grid.width,
+            scale: self.scale,
+        };
+        let height = grid.height.checked_mul(self.scale).ok_or_else(too_large)?;
+        let width = grid.width.checked_mul(self.scale).ok_or_else(too_large)?;
+        let mut pixels = Vec::with_capacity(height.checked_mul(width).ok_or_else(too_large)?);
+        for row in grid.rows_iter() {
+            let line: Vec<Colour> = row
+                .iter()
+                .flat_map(|&v| std::iter::repeat_n(self.map.encode(v), self.scale))
+                .collect();
+            for _ in 0..self.scale {
+                pixels.extend_from_slice(&line);
+            }
+        }
+        Ok((height, width, pixels))
+    }
+}
+
+/// Skips whitespace and `#` comments, which may appear anywhere in a header or plain raster.
+fn skip_blanks(bytes: &[u8], mut position: usize) -> usize {
+    while position < bytes.len() {
+        match bytes[position] {
+            b'#' => {
+                while position < bytes.len() && bytes[position] != b'\n' {
+                    position += 1;
+                }
+            }
+            b if b.is_ascii_whitespace() => position += 1,
+            _ => break,
+        }
+    }
+    position
+}
+
+/// Reads a whitespace separated token, returning it with the position just after it.
+fn next_token(bytes: &[u8], position: usize) -> Option<(&[u8], usize)> {
+    let start = skip_blanks(bytes, position);
+    let end = bytes[start..]
+        .iter()
+        .position(|b| b.is_ascii_whitespace() || *b == b'#')
+        .map_or(bytes.len(), |p| start + p);
+    (end > start).then_some((&bytes[start..end], end))
+}
+
+fn next_number(bytes: &[u8], position: usize) -> Result<(u32, usize), ImageError> {
+    let (token, end) = next_token(bytes, position).ok_or(ImageError::InvalidHeader {
+        found: String::new(),
+    })?;
+    let text = String::from_utf8_lossy(token);
+    let number = text.parse().map_err(|_| ImageError::InvalidHeader {
+        found: text.to_string(),
+    })?;
+    Ok((number, end))
+}
+
+/// Parses the magic number, size and maximum sample, returning the header and the position of
+/// the first pixel.
+fn parse_header(bytes: &[u8]) -> Result<(Header, usize), ImageError> {
+    let magic = match bytes {
+        [b'P', m @ (b'1' | b'2' | b'4' | b'5'), ..] => m - b'0',
+        _ => {
+            return Err(ImageError::UnknownFormat {
+                found: String::from_utf8_lossy(&bytes[..bytes.len().min(2)]).to_string(),
+            });
+        }
+    };
+
+    let (width, position) = next_number(bytes, 2)?;
+    let (height, mut position) = next_number(bytes, position)?;
+    let mut max = 1;
+    if matches!(magic, 2 | 5) {
+        (max, position) = next_number(bytes, position)?;
+        if max == 0 || max > u16::MAX as u32 {
+            return Err(ImageError::InvalidHeader {
+                found: max.to_string(),
+            });
+        }
+    }
+
+    // Raw images have exactly one whitespace byte between the header and the pixels
+    let raster = match magic {
+        1 | 2 => position,
+        _ => (position + 1).min(bytes.len()),
+    };
+
+    let (width, height) = (width as usize, height as usize);
+    if width.checked_mul(height).is_none() {
+        return Err(ImageError::InvalidHeader {
+            found: format!("{width} {height}"),
+        });
+    }
+
+    let header = Header {
+        magic,
+        width,
+        height,
+        max,
+    };
+    Ok((header, raster))
+}
+
+/// Reads every pixel of the raster as a grey level, 0 for black and 255 for white. The size in
+/// the header is checked against the length of the raster before anything is allocated.
+fn read_levels(header: &Header, raster: &[u8]) -> Result<Vec<u8>, ImageError> {
+    let expected = header.width * header.height;
+    let missing = |found| ImageError::MissingPixels { expected, found };
+    let bit_level = |set: bool| if set { 0 } else { 255 };
+    let row_bytes = header.width.div_ceil(8);
+    let sample_bytes = if header.max < 256 { 1 } else { 2 };
+
+    // Raw rasters have an exact length, plain ones take at least a byte per pixel
+    let capacity = match header.magic {
+        4 if raster.len() < row_bytes * header.height => {
+            return Err(missing(raster.len() / row_bytes * header.width));
+        }
+        5 if raster.len() / sample_bytes < expected => {
+            return Err(missing(raster.len() / sample_bytes));
+        }
+        1 | 2 => expected.min(raster.len()),
+        _ => expected,
+    };
+    let mut levels = Vec::with_capacity(capacity);
+
+    match header.magic {
+        1 => {
+            // Plain PBM pixels are single digits which need not be separated
+            let mut position = 0;
+            while levels.len() < expected {
+                position = skip_blanks(raster, position);
+                match raster.get(position) {
+                    Some(b'0') => levels.push(bit_level(false)),
+                    Some(b'1') => levels.push(bit_level(true)),
+                    Some(&other) => {
+                        return Err(invalid_sample(header, levels.len(), other as u32));
+                    }
+                    None => return Err(missing(levels.len())),
+                }
+                position += 1;
+            }
+        }
+        2 => {
+            let mut position = 0;
+            while levels.len() < expected {
+                let (token, end) = next_token(raster, position).ok_or(missing(levels.len()))?;
+                let sample: u32 = String::from_utf8_lossy(token)
+                    .parse()
+                    .map_err(|_| invalid_sample(header, levels.len(), u32::MAX))?;
+                levels.push(scale_sample(header, levels.len(), sample)?);
+                position = end;
+            }
+        }
+        4 => {
+            for packed in raster.chunks(row_bytes.max(1)).take(header.height) {
+                levels.extend(
+                    (0..header.width).map(|c| bit_level(packed[c / 8] & (0x80 >> (c % 8)) != 0)),
+                );
+            }
+        }
+        _ => {
+            for (i, sample) in raster.chunks(sample_bytes).take(expected).enumerate() {
+                let sample = sample.iter().fold(0u32, |acc, &b| (acc << 8) | b as u32);
+                levels.push(scale_sample(header, i, sample)?);
+            }
+        }
+    }
+
+    Ok(levels)
+}
+
+fn invalid_sample(header: &Header, index: usize, found: u32) -> ImageError {
+    ImageError::InvalidSample {
+        row: index / header.width,
+        column: index % header.width,
+        found,
+        max: header.max,
+    }
+}
+
+fn scale_sample(header: &Header, index: usize, sample: u32) -> Result<u8, ImageError> {
+    if sample > header.max {
+        return Err(invalid_sample(header, index, sample));
+    }
+    Ok((sample * 255 / header.max) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        BlackAndWhite, Colour, ColourMap, Encoding, Greyscale, ImageError, ImageFormat, Palette,
+    };
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, Edges};
+    use test_case::test_case;
+
+    fn bools(as_str: &str) -> Arr2d<bool> {
+        grid(as_str).map(|v| v == '#')
+    }
+
+    fn palette() -> Palette<char> {
+        Palette::new([
+            ('#', Colour::BLACK),
+            ('.', Colour::WHITE),
+            ('+', Colour::grey(128)),
+        ])
+    }
+
+    #[test]
+    fn test_plain_pbm() {
+        let input = bools("#..\n.#.");
+
+        let result = ImageFormat::default()
+            .with_encoding(Encoding::Plain)
+            .to_pbm(&input)
+            .unwrap();
+
+        assert_eq!(result, b"P1\n3 2\n100\n010\n");
+    }
+
+    #[test]
+    fn test_raw_pbm_pads_rows() {
+        let input = bools("#........#\n.#.......#");
+
+        let result = ImageFormat::default().to_pbm(&input).unwrap();
+
+        assert_eq!(
+            result,
+            [b"P4\n10 2\n".as_slice(), &[0x80, 0x40, 0x40, 0x40]].concat()
+        );
+    }
+
+    #[test]
+    fn test_plain_pgm() {
+        let input = grid("#+\n.#");
+
+        let result = ImageFormat::new(palette())
+            .with_encoding(Encoding::Plain)
+            .to_pgm(&input)
+            .unwrap();
+
+        assert_eq!(result, b"P2\n2 2\n255\n0 128 255 0\n");
+    }
+
+    #[test]
+    fn test_plain_lines_are_wrapped() {
+        let input = Arr2d::from_contents(vec![vec![200u8; 30]]);
+
+        let result = ImageFormat::new(Greyscale)
+            .with_encoding(Encoding::Plain)
+            .to_pgm(&input)
+            .unwrap();
+
+        let text = String::from_utf8(result).unwrap();
+        assert!(text.lines().all(|line| line.len() <= 70));
+        assert_eq!(
+            ImageFormat::new(Greyscale).parse::<u8>(text.as_bytes()),
+            Ok(input)
+        );
+    }
+
+    #[test]
+    fn test_scale() {
+        let input = bools("#.");
+
+        let result = ImageFormat::default()
+            .with_encoding(Encoding::Plain)
+            .with_scale(2)
+            .to_pbm(&input)
+            .unwrap();
+
+        assert_eq!(result, b"P1\n4 2\n1100\n1100\n");
+    }
+
+    #[test]
+    fn test_scale_overflow() {
+        let input: Arr2d<bool> = Arr2d {
+            width: 0,
+            height: usize::MAX / 2 + 1,
+            contents: Vec::new(),
+            edges: Edges::Bounded,
+        };
+
+        let result = ImageFormat::default().with_scale(2).to_pbm(&input);
+
+        assert_eq!(
+            result,
+            Err(ImageError::TooLarge {
+                height: usize::MAX / 2 + 1,
+                width: 0,
+                scale: 2
+            })
+        );
+    }
+
+    #[test_case(Encoding::Plain ; "plain")]
+    #[test_case(Encoding::Raw ; "raw")]
+    fn test_round_trip(encoding: Encoding) {
+        let input = grid("#+.#\n.#+.\n++#.");
+        let format = ImageFormat::new(palette())
+            .with_encoding(encoding)
+            .with_scale(3);
+
+        let pgm = format.parse(&format.to_pgm(&input).unwrap());
+        let pbm = ImageFormat::default()
+            .with_encoding(encoding)
+            .parse::<bool>(
+                &ImageFormat::default()
+                    .to_pbm(&input.map(|v| v == '#'))
+                    .unwrap(),
+            );
+
+        assert_eq!(pgm, Ok(input.clone()));
+        assert_eq!(pbm, Ok(input.map(|v| v == '#')));
+    }
+
+    #[test_case(b"P1 5 5 0000000000000000000000000" ; "both sides")]
+    #[test_case(b"P1 4 3 000000000000" ; "one side")]
+    fn test_parse_uneven_scale(input: &[u8]) {
+        let result = ImageFormat::default().with_scale(2).parse::<bool>(input);
+
+        assert!(matches!(
+            result,
+            Err(ImageError::UnevenScale { scale: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_comments_and_unseparated_bits() {
+        let input = b"P1\n# drawn by hand\n3 2 # size\n1 0 1\n010";
+
+        let result = ImageFormat::default().parse(input);
+
+        assert_eq!(result, Ok(bools("#.#\n.#.")));
+    }
+
+    #[test]
+    fn test_parse_scales_samples() {
+        let input = b"P2 3 1 4 0 2 4";
+
+        let result = ImageFormat::new(Greyscale).parse(input);
+
+        assert_eq!(result, Ok(Arr2d::from_contents(vec![vec![0u8, 127, 255]])));
+    }
+
+    #[test]
+    fn test_parse_sixteen_bit_samples() {
+        let input = [b"P5\n2 1\n65535\n".as_slice(), &[0xff, 0xff, 0x00, 0x00]].concat();
+
+        let result = ImageFormat::new(Greyscale).parse(&input);
+
+        assert_eq!(result, Ok(Arr2d::from_contents(vec![vec![255u8, 0]])));
+    }
+
+    #[test]
+    fn test_palette_picks_nearest_colour() {
+        assert_eq!(palette().decode(Colour::grey(100)), Some('+'));
+        assert_eq!(palette().decode(Colour::new(250, 240, 255)), Some('.'));
+        assert_eq!(BlackAndWhite.decode(Colour::grey(90)), Some(true));
+    }
+
+    #[test_case(b"P3\n1 1\n", ImageError::UnknownFormat { found: "P3".to_string() })]
+    #[test_case(b"P1\n2 x\n", ImageError::InvalidHeader { found: "x".to_string() })]
+    #[test_case(b"P2\n1 1\n0\n0", ImageError::InvalidHeader { found: "0".to_string() })]
+    #[test_case(b"P1\n2 2\n101", ImageError::MissingPixels { expected: 4, found: 3 })]
+    #[test_case(b"P4\n9 2\n\x80\x00", ImageError::MissingPixels { expected: 18, found: 9 })]
+    #[test_case(b"P4\n100000 100000\n\x80", ImageError::MissingPixels { expected: 10_000_000_000, found: 0 })]
+    #[test_case(b"P5\n100000 100000\n255\n\x80\x00", ImageError::MissingPixels { expected: 10_000_000_000, found: 2 })]
+    #[test_case(b"P1 4294967295 4294967295 0", ImageError::MissingPixels { expected: 18_446_744_065_119_617_025, found: 1 })]
+    #[test_case(b"P2\n2 1\n15\n3 16", ImageError::InvalidSample { row: 0, column: 1, found: 16, max: 15 })]
+    #[test_case(b"P1\n2 1\n12", ImageError::InvalidSample { row: 0, column: 1, found: 50, max: 1 })]
+    fn test_parse_errors(input: &[u8], expected: ImageError) {
+        let result = ImageFormat::default().parse::<bool>(input);
+
+        assert_eq!(result, Err(expected));
+    }
+
+    struct OnlyBlack;
+
+    impl ColourMap<bool> for OnlyBlack {
+        fn encode(&self, _: bool) -> Colour {
+            Colour::BLACK
+        }
+
+        fn decode(&self, colour: Colour) -> Option<bool> {
+            (colour == Colour::BLACK).then_some(true)
+        }
+    }
+
+    #[test]
+    fn test_unmapped_colour() {
+        let result = ImageFormat::new(OnlyBlack).parse(b"P1 2 1 10");
+
+        assert_eq!(
+            result,
+            Err(ImageError::UnmappedColour {
+                row: 0,
+                column: 1,
+                colour: Colour::WHITE
+            })
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "pixel (0, 1): no value for colour (255, 255, 255)"
+        );
+    }
+
+    #[cfg(feature = "png")]
+    #[test]
+    fn test_png() {
+        let input = grid("#+\n..");
+
+        let result = ImageFormat::new(palette())
+            .with_scale(2)
+            .to_png(&input)
+            .unwrap();
+
+        let mut reader = png::Decoder::new(std::io::Cursor::new(result))
+            .read_info()
+            .unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (4, 4));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+        assert_eq!(
+            &pixels[..12],
+            &[0, 0, 0, 0, 0, 0, 128, 128, 128, 128, 128, 128]
+        );
+        assert!(pixels[24..].iter().all(|&p| p == 255));
+    }
+}
@@ -1,23 +1,189 @@
-use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Display;
-use std::hash::Hash;
+use std::hash::{Hash, Hasher};
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
 
+mod bits;
+mod coord;
+mod diff;
+mod edges;
+mod grid;
+mod image;
+mod neighbourhood;
+#[cfg(feature = "rayon")]
+mod parallel;
+mod path;
+mod regions;
+mod rle;
+#[cfg(feature = "serde")]
+mod serialize;
+mod sparse;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+mod text;
+mod transform;
+mod view;
+
+pub use bits::BitGrid;
+pub use coord::{Coord, Direction};
+pub use diff::Change;
+pub use edges::Edges;
+pub use grid::{Grid, GridMut, flood_fill, perimeter};
+pub use image::{
+    BlackAndWhite, Colour, ColourMap, Encoding, Greyscale, ImageError, ImageFormat, Palette,
+};
+pub use neighbourhood::Neighbourhood;
+pub use path::Path;
+pub use regions::{Region, RegionId, Regions};
+pub use rle::{RleFormat, is_rle};
+pub use sparse::SparseGrid;
+pub use text::{CellCodec, CharCodec, DisplayCodec, TextFormat, TokenCodec};
+pub use transform::Rect;
+pub use view::View;
+
+/// Errors raised while parsing text into grids. Line and column numbers are 1-based and refer to
+/// the original input, blank lines included, so they can be matched up with a fixture file.
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
-    InvalidCharacter,
-    NotEnoughLines,
-    NotEnoughChars,
-    InvalidValue,
+    InvalidCharacter {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    NotEnoughLines {
+        expected: usize,
+        found: usize,
+    },
+    NotEnoughChars {
+        line: usize,
+        column: usize,
+    },
+    InvalidValue {
+        line: usize,
+        found: String,
+    },
+    RaggedRow {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidToken {
+        line: usize,
+        column: usize,
+        found: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidCharacter {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: invalid character {found:?}"
+            ),
+            ParseError::NotEnoughLines { expected, found } => {
+                write!(f, "expected at least {expected} lines, found {found}")
+            }
+            ParseError::NotEnoughChars { line, column } => {
+                write!(f, "line {line}, column {column}: unexpected end of line")
+            }
+            ParseError::InvalidValue { line, found } => {
+                write!(f, "line {line}: invalid value {found:?}")
+            }
+            ParseError::RaggedRow {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line}: expected a row of {expected} cells, found {found}"
+            ),
+            ParseError::InvalidToken {
+                line,
+                column,
+                found,
+            } => write!(f, "line {line}, column {column}: invalid token {found:?}"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Errors raised when addressing positions or regions of a grid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arr2dError {
+    OutOfBounds {
+        row: isize,
+        col: isize,
+        rows: usize,
+        cols: usize,
+    },
+    RectOutOfBounds {
+        rect: Rect,
+        rows: usize,
+        cols: usize,
+    },
+    SizeMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    PatchConflict {
+        row: usize,
+        col: usize,
+    },
+}
+
+impl fmt::Display for Arr2dError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Arr2dError::OutOfBounds {
+                row,
+                col,
+                rows,
+                cols,
+            } => write!(
+                f,
+                "position ({row}, {col}) is outside of a {rows}x{cols} grid"
+            ),
+            Arr2dError::RectOutOfBounds { rect, rows, cols } => write!(
+                f,
+                "{}x{} region at ({}, {}) extends beyond a {rows}x{cols} grid",
+                rect.height, rect.width, rect.row, rect.column
+            ),
+            Arr2dError::SizeMismatch { expected, found } => write!(
+                f,
+                "expected a {}x{} grid, found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            Arr2dError::PatchConflict { row, col } => write!(
+                f,
+                "position ({row}, {col}) does not hold the value the patch expects"
+            ),
+        }
+    }
 }
 
-pub trait CellValue: TryFrom<char> + Into<char> + PartialEq + Copy + Hash + Display {}
+impl std::error::Error for Arr2dError {}
+
+/// Error for cell values that cannot be built from a character. Parsers convert this into a
+/// [`ParseError::InvalidCharacter`] once they know where the character was found.
+#[derive(Debug, PartialEq)]
+pub struct InvalidChar(pub char);
+
+pub trait CellValue: PartialEq + Copy + Hash {}
 
-impl<T> CellValue for T where T: TryFrom<char> + Into<char> + PartialEq + Copy + Hash + Display {}
+impl<T> CellValue for T where T: PartialEq + Copy + Hash {}
 
-#[derive(Eq, Hash, Debug)]
+/// A view of a single position in an [`Arr2d`]. Cells are not stored, they are built on demand
+/// from the flat contents. A cell is identified by its position, so cells compare and hash by
+/// position and value alone.
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub struct Cell<T: CellValue> {
-    id: u32,
     row: usize,
     column: usize,
     value: T,
@@ -25,7 +191,7 @@ pub struct Cell<T: CellValue> {
 
 impl<T> fmt::Display for Cell<T>
 where
-    T: CellValue,
+    T: CellValue + Display,
 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.value)
@@ -36,8 +202,10 @@ impl<T> Cell<T>
 where
     T: CellValue,
 {
-    pub fn id(&self) -> u32 {
-        self.id
+    /// The position of the cell, which is the same for a cell built with [`Cell::from`] as for
+    /// one taken from a grid.
+    pub fn id(&self) -> Coord {
+        Coord::new(self.row as isize, self.column as isize)
     }
     pub fn row(&self) -> usize {
         self.row
@@ -50,44 +218,88 @@ where
     }
 
     pub fn from(row: usize, column: usize, value: T) -> Cell<T> {
-        Cell {
-            id: 0,
-            row,
-            column,
-            value,
-        }
+        Cell { row, column, value }
+    }
+}
+
+/// A rectangular grid of values, stored row-major in a single contiguous `Vec`.
+///
+/// Grids compare and hash by their dimensions and contents alone, whatever their edges.
+#[derive(Debug, Eq, Clone)]
+pub struct Arr2d<T: CellValue> {
+    width: usize,
+    height: usize,
+    contents: Vec<T>,
+    /// What neighbour lookups see beyond the grid. This is not part of the grid's value.
+    edges: Edges<T>,
+}
+
+impl<T: CellValue> PartialEq for Arr2d<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height && self.contents == other.contents
+    }
+}
+
+impl<T: CellValue> Hash for Arr2d<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.width.hash(state);
+        self.height.hash(state);
+        self.contents.hash(state);
+    }
+}
+
+impl<T> fmt::Display for Arr2d<T>
+where
+    T: CellValue + TryFrom<char> + Into<char>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&TextFormat::default().format(self))
     }
 }
 
-impl<T> PartialEq for Cell<T>
+impl<T> Default for Arr2d<T>
 where
     T: CellValue,
 {
-    fn eq(&self, b: &Cell<T>) -> bool {
-        self.row == b.row && self.column == b.column && self.value == b.value
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-#[derive(Debug, Hash, Eq)]
-pub struct Arr2d<T: CellValue> {
-    contents: Vec<Vec<Cell<T>>>,
+impl<T> Index<(usize, usize)> for Arr2d<T>
+where
+    T: CellValue,
+{
+    type Output = T;
+
+    fn index(&self, (row, col): (usize, usize)) -> &T {
+        match self.try_get(row, col) {
+            Ok(value) => value,
+            Err(e) => panic!("{e}"),
+        }
+    }
 }
 
-impl<T> fmt::Display for Arr2d<T>
+impl<T> IndexMut<(usize, usize)> for Arr2d<T>
 where
     T: CellValue,
 {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for row in &self.contents {
-            for (i, val) in row.iter().enumerate() {
-                if i > 0 {
-                    write!(f, " ")?;
-                }
-                write!(f, "{}", val)?;
-            }
-            write!(f, "\n")?;
+    fn index_mut(&mut self, (row, col): (usize, usize)) -> &mut T {
+        match self.get_mut(row, col) {
+            Ok(value) => value,
+            Err(e) => panic!("{e}"),
         }
-        Ok(())
+    }
+}
+
+impl<T> FromStr for Arr2d<T>
+where
+    T: CellValue + TryFrom<char> + Into<char>,
+{
+    type Err = ParseError;
+
+    fn from_str(as_str: &str) -> Result<Arr2d<T>, ParseError> {
+        TextFormat::default().parse(as_str)
     }
 }
 
@@ -97,206 +309,346 @@ where
 {
     pub fn new() -> Arr2d<T> {
         Arr2d {
+            width: 0,
+            height: 0,
             contents: Vec::new(),
+            edges: Edges::Bounded,
         }
     }
 
+    /// Builds a grid from a list of rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows are not all the same length.
     pub fn from_contents(contents: Vec<Vec<T>>) -> Arr2d<T> {
-        let mut id = 0;
+        let height = contents.len();
+        let width = contents.first().map_or(0, |row| row.len());
+        assert!(
+            contents.iter().all(|row| row.len() == width),
+            "Arr2d rows must all be the same length"
+        );
 
         Arr2d {
-            contents: contents
-                .iter()
-                .enumerate()
-                .map(|(row, row_c)| {
-                    row_c
-                        .iter()
-                        .enumerate()
-                        .map(|(column, &value)| {
-                            id += 1;
-                            return Cell {
-                                id,
-                                row,
-                                column,
-                                value,
-                            };
-                        })
-                        .collect()
-                })
-                .collect(),
+            width,
+            height,
+            contents: contents.into_iter().flatten().collect(),
+            edges: Edges::Bounded,
         }
     }
 
-    pub fn from_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Arr2d<T>, ParseError> {
-        let mut rows: Vec<Vec<T>> = Vec::new();
-
-        for row in lines {
-            let mut cells: Vec<T> = Vec::new();
-            for cell in row.trim().chars() {
-                match <T>::try_from(cell) {
-                    Ok(v) => cells.push(v),
-                    Err(_) => return Err(ParseError::InvalidCharacter),
-                }
-            }
-            rows.push(cells);
-        }
+    fn index_of(&self, row: usize, column: usize) -> usize {
+        row * self.width + column
+    }
 
-        Ok(Arr2d::from_contents(rows))
+    fn checked_index(&self, row: usize, col: usize) -> Result<usize, Arr2dError> {
+        if row < self.height && col < self.width {
+            Ok(self.index_of(row, col))
+        } else {
+            Err(Arr2dError::OutOfBounds {
+                row: row as isize,
+                col: col as isize,
+                rows: self.height,
+                cols: self.width,
+            })
+        }
     }
 
-    pub fn from_str(as_str: &str) -> Result<Arr2d<T>, ParseError> {
-        Self::from_lines(
-            as_str
-                .split("\n")
-                .map(|line| line.trim())
-                .filter(|line| !line.is_empty()),
-        )
+    pub(crate) fn check_rect(&self, rect: Rect) -> Result<(), Arr2dError> {
+        let fits = |start: usize, length: usize, size: usize| {
+            start.checked_add(length).is_some_and(|end| end <= size)
+        };
+        if !fits(rect.row, rect.height, self.height) || !fits(rect.column, rect.width, self.width) {
+            return Err(Arr2dError::RectOutOfBounds {
+                rect,
+                rows: self.height,
+                cols: self.width,
+            });
+        }
+        Ok(())
     }
 
-    pub fn get_cell(&self, row: usize, column: usize) -> Result<&Cell<T>, &str> {
-        match &self.contents.get(row) {
-            Some(r) => match r.get(column) {
-                Some(c) => Ok(c),
-                None => return Err("Invalid column index"),
-            },
-            None => return Err("Invalid row index"),
+    fn cell_at(&self, index: usize) -> Cell<T> {
+        Cell {
+            row: index / self.width,
+            column: index % self.width,
+            value: self.contents[index],
         }
     }
 
-    pub fn all_cells(&self) -> impl Iterator<Item = &Cell<T>> {
-        self.contents.iter().flat_map(|row| row.into_iter())
+    fn index_of_cell(&self, cell: &Cell<T>) -> usize {
+        self.index_of(cell.row, cell.column)
     }
 
-    pub fn get_neighbours(&self, row: usize, column: usize) -> impl Iterator<Item = &Cell<T>> {
-        [
-            (Some(row), column.checked_sub(1)),
-            (Some(row), column.checked_add(1)),
-            (row.checked_sub(1), Some(column)),
-            (row.checked_add(1), Some(column)),
-        ]
-        .into_iter()
-        .filter_map(|(r, c)| {
-            if let (Some(r), Some(c)) = (r, c) {
-                self.get_cell(r, c).ok()
-            } else {
-                None
-            }
+    pub fn get_cell(&self, row: usize, column: usize) -> Result<Cell<T>, Arr2dError> {
+        Ok(self.cell_at(self.checked_index(row, column)?))
+    }
+
+    pub fn all_cells(&self) -> impl Iterator<Item = Cell<T>> + '_ {
+        (0..self.contents.len()).map(|index| self.cell_at(index))
+    }
+
+    /// Mutable references to every value, in row-major order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.contents.iter_mut()
+    }
+
+    pub fn rows_iter(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |row| &self.contents[row * self.width..][..self.width])
+    }
+
+    pub fn columns_iter(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |column| self.contents.iter().skip(column).step_by(self.width))
+    }
+
+    /// Builds a grid of the same size by applying `f` to every value. Padded edges are mapped too.
+    pub fn map<U: CellValue>(&self, f: impl Fn(T) -> U) -> Arr2d<U> {
+        Arr2d {
+            width: self.width,
+            height: self.height,
+            contents: self.contents.iter().map(|&v| f(v)).collect(),
+            edges: self.edges.map(&f),
+        }
+    }
+
+    /// Combines two grids of the same size value by value. The result wraps like this grid, and
+    /// has padded edges only if both grids do.
+    pub fn zip_with<U: CellValue, V: CellValue>(
+        &self,
+        other: &Arr2d<U>,
+        f: impl Fn(T, U) -> V,
+    ) -> Result<Arr2d<V>, Arr2dError> {
+        if self.width != other.width || self.height != other.height {
+            return Err(Arr2dError::SizeMismatch {
+                expected: (self.height, self.width),
+                found: (other.height, other.width),
+            });
+        }
+
+        let edges = match (self.edges, other.edges) {
+            (Edges::Padded(a), Edges::Padded(b)) => Edges::Padded(f(a, b)),
+            (Edges::Torus, _) => Edges::Torus,
+            (Edges::KleinBottle, _) => Edges::KleinBottle,
+            _ => Edges::Bounded,
+        };
+
+        Ok(Arr2d {
+            width: self.width,
+            height: self.height,
+            contents: self
+                .contents
+                .iter()
+                .zip(other.contents.iter())
+                .map(|(&a, &b)| f(a, b))
+                .collect(),
+            edges,
         })
     }
 
-    pub fn get_perimeter(
+    /// Fills `next` by calling `f` with this grid and every position, so each new value can be
+    /// worked out from the values around it. This grid is only read, so the two act as a pair of
+    /// buffers: swap them and repeat to run a cellular automaton.
+    pub fn stencil_into<U: CellValue>(
         &self,
+        next: &mut Arr2d<U>,
+        f: impl Fn(&Arr2d<T>, usize, usize) -> U,
+    ) -> Result<(), Arr2dError> {
+        self.check_same_size(next)?;
+
+        for (row, values) in next.contents.chunks_mut(self.width.max(1)).enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = f(self, row, column);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_neighbours<'a>(
+        &'a self,
         row: usize,
         column: usize,
-    ) -> Result<impl Iterator<Item = &Cell<T>>, &str> {
-        let mut seen = HashSet::new();
-        let start_cell = match self.get_cell(row, column) {
-            Ok(c) => c,
-            Err(e) => return Err(e),
-        };
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = Cell<T>> + 'a {
+        let offsets = neighbourhood.offsets(row);
+        (0..offsets.len()).filter_map(move |i| {
+            let (r, c) = self.resolve_offset(row, column, offsets[i])?;
+            Some(self.cell_at(self.index_of(r, c)))
+        })
+    }
 
-        match self.flood_fill(row, column) {
-            Ok(c) => Ok(c
-                .flat_map(|cell| self.get_neighbours(cell.row, cell.column))
-                .filter(|cell| cell.value != start_cell.value)
-                .filter(move |cell| seen.insert(cell.id))),
-            Err(e) => Err(e),
-        }
+    /// The values of the neighbours of a position. Unlike [`Arr2d::get_neighbours`] this includes
+    /// the fixed value of any off-grid neighbours when the edges are [`Edges::Padded`].
+    pub fn neighbour_values<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = T> + 'a {
+        let offsets = neighbourhood.offsets(row);
+        (0..offsets.len()).filter_map(move |i| {
+            match (self.resolve_offset(row, column, offsets[i]), self.edges) {
+                (Some((r, c)), _) => Some(self.contents[self.index_of(r, c)]),
+                (None, Edges::Padded(value)) => Some(value),
+                (None, _) => None,
+            }
+        })
     }
 
-    pub fn flood_fill(
+    fn resolve_offset(
         &self,
         row: usize,
         column: usize,
-    ) -> Result<impl Iterator<Item = &Cell<T>>, &str> {
-        let mut to_visit: Vec<&Cell<T>> = Vec::new();
-        let mut ids_seen: HashSet<u32> = HashSet::new();
-        let start_cell = match self.get_cell(row, column) {
-            Ok(c) => c,
-            Err(e) => return Err(e),
-        };
-        to_visit.push(start_cell);
-        ids_seen.insert(start_cell.id);
+        (dr, dc): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        self.edges.resolve(
+            row as isize + dr,
+            column as isize + dc,
+            self.height,
+            self.width,
+        )
+    }
 
-        Ok(std::iter::from_fn(move || match to_visit.pop() {
-            Some(cell) => {
-                self.get_neighbours(cell.row, cell.column)
-                    .filter(|c| ids_seen.insert(c.id) && c.value == start_cell.value)
-                    .for_each(|c| to_visit.push(c));
+    pub fn get_perimeter<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> Result<impl Iterator<Item = Cell<T>> + 'a, Arr2dError> {
+        self.checked_index(row, column)?;
+        Ok(
+            grid::perimeter(self, row as isize, column as isize, neighbourhood)
+                .map(|(r, c)| self.cell_at(self.index_of(r as usize, c as usize))),
+        )
+    }
 
-                return Some(cell);
-            }
-            None => None,
-        }))
+    pub fn flood_fill<'a>(
+        &'a self,
+        row: usize,
+        column: usize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> Result<impl Iterator<Item = Cell<T>> + 'a, Arr2dError> {
+        self.checked_index(row, column)?;
+        Ok(
+            grid::flood_fill(self, row as isize, column as isize, neighbourhood)
+                .map(|(r, c)| self.cell_at(self.index_of(r as usize, c as usize))),
+        )
     }
 
     pub fn expand(&self, width: usize, height: usize, filler: T) -> Arr2d<T> {
-        let mut contents: Vec<Vec<T>> = self
-            .contents
-            .iter()
-            .map(|v| v.iter().map(|c| c.value).collect())
-            .collect();
+        let mut contents = vec![filler; width * height];
 
-        for row in contents.iter_mut() {
-            while row.len() < width {
-                row.push(filler);
-            }
-            while row.len() > width {
-                row.pop();
+        for row in 0..self.height.min(height) {
+            for column in 0..self.width.min(width) {
+                contents[row * width + column] = self.contents[self.index_of(row, column)];
             }
         }
 
-        while contents.len() < height {
-            contents.push(vec![filler; width]);
-        }
-        while contents.len() > height {
-            contents.pop();
+        Arr2d {
+            width,
+            height,
+            contents,
+            edges: self.edges,
         }
+    }
 
-        Arr2d::from_contents(contents)
+    /// Sets what lies beyond the edges of the grid for neighbour lookups, flood fills and
+    /// perimeters.
+    pub fn with_edges(mut self, edges: Edges<T>) -> Arr2d<T> {
+        self.edges = edges;
+        self
     }
 
-    pub fn rows(&self) -> usize {
-        self.contents.len()
+    pub fn edges(&self) -> Edges<T> {
+        self.edges
     }
 
-    pub fn columns(&self, row: usize) -> usize {
-        self.contents[row].len()
+    pub fn width(&self) -> usize {
+        self.width
     }
 
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the grid, see [`Arr2d::try_get`].
     pub fn get(&self, row: usize, col: usize) -> &T {
-        &self.contents[row][col].value
+        &self[(row, col)]
     }
 
+    /// # Panics
+    ///
+    /// Panics if the position is outside of the grid, see [`Arr2d::try_set`].
     pub fn set(&mut self, row: usize, col: usize, value: T) {
-        self.contents[row][col].value = value;
+        self[(row, col)] = value;
     }
 
-    pub fn to_str(&self) -> String {
-        let mut as_str = String::new();
-        for row in &self.contents {
-            for cell in row {
-                as_str.push(cell.value.into());
-            }
-            as_str.push_str("\n");
-        }
+    pub fn try_get(&self, row: usize, col: usize) -> Result<&T, Arr2dError> {
+        Ok(&self.contents[self.checked_index(row, col)?])
+    }
 
-        as_str
+    pub fn get_mut(&mut self, row: usize, col: usize) -> Result<&mut T, Arr2dError> {
+        let index = self.checked_index(row, col)?;
+        Ok(&mut self.contents[index])
+    }
+
+    pub fn try_set(&mut self, row: usize, col: usize, value: T) -> Result<(), Arr2dError> {
+        *self.get_mut(row, col)? = value;
+        Ok(())
     }
 }
 
-impl<T: CellValue> PartialEq for Arr2d<T> {
-    fn eq(&self, other: &Self) -> bool {
-        self.contents == other.contents
+/// Parsing and printing in the default text format, one character per cell and one row per line.
+/// Use a [`TextFormat`] for anything else.
+impl<T> Arr2d<T>
+where
+    T: CellValue + TryFrom<char> + Into<char>,
+{
+    /// Parses one row per line, numbering the lines from 1 for error reporting.
+    pub fn from_lines<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Arr2d<T>, ParseError> {
+        Self::from_numbered_lines(lines.enumerate().map(|(i, line)| (i + 1, line)))
+    }
+
+    /// Parses one row per line, where each line is paired with its line number in the original
+    /// input. Every row must be the same width as the first, otherwise a
+    /// [`ParseError::RaggedRow`] is returned.
+    pub fn from_numbered_lines<'a>(
+        lines: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<Arr2d<T>, ParseError> {
+        TextFormat::default().parse_lines(lines)
+    }
+
+    pub fn to_str(&self) -> String {
+        TextFormat::default().format(self)
+    }
+}
+
+/// Helpers shared by the tests of every module.
+#[cfg(test)]
+mod test_helpers {
+    use crate::Arr2d;
+    use std::str::FromStr;
+
+    pub(crate) fn grid(as_str: &str) -> Arr2d<char> {
+        Arr2d::from_str(as_str).expect("Arr2d should have parsed test input")
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::Arr2d;
+    use super::Arr2dError;
     use super::Cell;
+    use super::Coord;
+    use super::Edges;
+    use super::InvalidChar;
+    use super::Neighbourhood;
     use super::ParseError;
+    use super::Rect;
+    use std::collections::HashSet;
     use std::fmt;
+    use std::str::FromStr;
     use test_case::test_case;
 
     type Coordinate = (usize, usize);
@@ -319,15 +671,19 @@ mod tests {
     }
 
     impl TryFrom<char> for TestBool {
-        type Error = ParseError;
+        type Error = InvalidChar;
 
-        fn try_from(c: char) -> Result<TestBool, ParseError> {
-            Ok(TestBool(c == 'y'))
+        fn try_from(c: char) -> Result<TestBool, InvalidChar> {
+            match c {
+                'y' => Ok(TestBool(true)),
+                'n' => Ok(TestBool(false)),
+                _ => Err(InvalidChar(c)),
+            }
         }
     }
-    impl Into<char> for TestBool {
-        fn into(self) -> char {
-            if self.0 { 'y' } else { 'n' }
+    impl From<TestBool> for char {
+        fn from(value: TestBool) -> char {
+            if value.0 { 'y' } else { 'n' }
         }
     }
 
@@ -372,12 +728,55 @@ mod tests {
         assert_eq!(result, expected);
     }
 
-    #[test_case((0, 0), vec![(0, 1, true), (1, 0, false)] )]
-    #[test_case((1, 1), vec![(0, 1, true), (2, 1, false), (1, 0, false), (1, 2, false)] )]
-    #[test_case((2, 2), vec![(2, 1, false), (1, 2, false), (2, 3, true)] )]
-    #[test_case((2, 4), vec![(2, 3, true), (1, 4, true)] )]
-    #[test_case((0, 4), vec![(0, 3, false), (1, 4, true)] )]
-    fn test_get_neighbours((row, column): Coordinate, expected: Vec<ExpectedCell>) {
+    #[test]
+    fn test_from_str_dimensions() {
+        let result: Arr2d<TestBool> = Arr2d::from_str("yyynn\nnynny\nynnyn\n").unwrap();
+
+        assert_eq!(result.width(), 5);
+        assert_eq!(result.height(), 3);
+    }
+
+    #[test_case("yyy\nyy\nyyy", ParseError::RaggedRow { line: 2, expected: 3, found: 2 })]
+    #[test_case("yy\n\nyyy", ParseError::RaggedRow { line: 3, expected: 2, found: 3 })]
+    #[test_case("yyy\nynx", ParseError::InvalidCharacter { line: 2, column: 3, found: 'x' })]
+    #[test_case("\n    yyy\n    nxn", ParseError::InvalidCharacter { line: 3, column: 6, found: 'x' })]
+    fn test_from_str_errors(input: &str, expected: ParseError) {
+        let result: Result<Arr2d<TestBool>, ParseError> = Arr2d::from_str(input);
+
+        assert_eq!(result, Err(expected));
+    }
+
+    #[test]
+    fn test_from_lines_numbers_from_one() {
+        let result: Result<Arr2d<TestBool>, ParseError> =
+            Arr2d::from_lines(["yn", "ny", "n"].into_iter());
+
+        assert_eq!(
+            result,
+            Err(ParseError::RaggedRow {
+                line: 3,
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test_case(Neighbourhood::VonNeumann, (0, 0), vec![(0, 1, true), (1, 0, false)] )]
+    #[test_case(Neighbourhood::VonNeumann, (1, 1), vec![(0, 1, true), (2, 1, false), (1, 0, false), (1, 2, false)] )]
+    #[test_case(Neighbourhood::VonNeumann, (2, 2), vec![(2, 1, false), (1, 2, false), (2, 3, true)] )]
+    #[test_case(Neighbourhood::VonNeumann, (2, 4), vec![(2, 3, true), (1, 4, true)] )]
+    #[test_case(Neighbourhood::VonNeumann, (0, 4), vec![(0, 3, false), (1, 4, true)] )]
+    #[test_case(Neighbourhood::Moore, (1, 1), vec![(0, 0, true), (0, 1, true), (0, 2, true), (1, 0, false), (1, 2, false), (2, 0, true), (2, 1, false), (2, 2, false)] )]
+    #[test_case(Neighbourhood::Moore, (0, 4), vec![(0, 3, false), (1, 3, false), (1, 4, true)] )]
+    #[test_case(Neighbourhood::MooreRadius(2), (0, 4), vec![(0, 2, true), (0, 3, false), (1, 2, false), (1, 3, false), (1, 4, true), (2, 2, false), (2, 3, true), (2, 4, false)] )]
+    #[test_case(Neighbourhood::HexOddRows, (0, 0), vec![(0, 1, true), (1, 0, false)] )]
+    #[test_case(Neighbourhood::HexOddRows, (1, 1), vec![(0, 1, true), (0, 2, true), (1, 0, false), (1, 2, false), (2, 1, false), (2, 2, false)] )]
+    #[test_case(Neighbourhood::Custom(vec![(1, 2), (2, 1), (-1, 2)]), (0, 0), vec![(1, 2, false), (2, 1, false)] )]
+    fn test_get_neighbours(
+        neighbourhood: Neighbourhood,
+        (row, column): Coordinate,
+        expected: Vec<ExpectedCell>,
+    ) {
         // Given
         let input: Arr2d<TestBool> = Arr2d::from_str(
             r#"
@@ -389,15 +788,21 @@ mod tests {
         .expect("Arr2d should have parsed test input");
 
         // When
-        let result: Vec<&Cell<TestBool>> = input.get_neighbours(row, column).collect();
+        let result: Vec<Cell<TestBool>> =
+            input.get_neighbours(row, column, &neighbourhood).collect();
 
         // Then
         assert_cells(&result, expected);
     }
 
-    #[test_case((1, 1), vec![(1, 0, false), (0, 3, false), (2, 1, false), (1, 2, false)])]
-    #[test_case((2, 1), vec![(2, 0, true), (2, 3, true), (1, 1, true), (1, 4, true), (0, 2, true)])]
-    fn test_get_perimeter((row, column): Coordinate, expected: Vec<ExpectedCell>) {
+    #[test_case(Neighbourhood::VonNeumann, (1, 1), vec![(1, 0, false), (0, 3, false), (2, 1, false), (1, 2, false)])]
+    #[test_case(Neighbourhood::VonNeumann, (2, 1), vec![(2, 0, true), (2, 3, true), (1, 1, true), (1, 4, true), (0, 2, true)])]
+    #[test_case(Neighbourhood::Moore, (1, 1), vec![(1, 0, false), (1, 2, false), (2, 1, false), (2, 2, false), (0, 3, false), (1, 3, false)])]
+    fn test_get_perimeter(
+        neighbourhood: Neighbourhood,
+        (row, column): Coordinate,
+        expected: Vec<ExpectedCell>,
+    ) {
         // Given
         let input: Arr2d<TestBool> = Arr2d::from_str(
             r#"
@@ -409,7 +814,7 @@ mod tests {
         .expect("Arr2d should have parsed test input");
 
         // When
-        let result: Vec<&Cell<TestBool>> = match input.get_perimeter(row, column) {
+        let result: Vec<Cell<TestBool>> = match input.get_perimeter(row, column, &neighbourhood) {
             Ok(i) => i.collect(),
             _ => panic!("Could not flood fill"),
         };
@@ -418,9 +823,15 @@ mod tests {
         assert_cells(&result, expected);
     }
 
-    #[test_case((1, 1, true), vec![(1, 1), (0, 0), (0, 1), (0, 2)])]
-    #[test_case((2, 1, false), vec![(2, 1), (2, 2), (1, 2), (1, 3), (0, 3), (0, 4)])]
-    fn test_flood_fill((row, column, value): ExpectedCell, expected: Vec<Coordinate>) {
+    #[test_case(Neighbourhood::VonNeumann, (1, 1, true), vec![(1, 1), (0, 0), (0, 1), (0, 2)])]
+    #[test_case(Neighbourhood::VonNeumann, (2, 1, false), vec![(2, 1), (2, 2), (1, 2), (1, 3), (0, 3), (0, 4)])]
+    #[test_case(Neighbourhood::Moore, (1, 1, true), vec![(1, 1), (0, 0), (0, 1), (0, 2), (2, 0)])]
+    #[test_case(Neighbourhood::Moore, (2, 1, false), vec![(0, 3), (0, 4), (1, 0), (1, 2), (1, 3), (2, 1), (2, 2), (2, 4)])]
+    fn test_flood_fill(
+        neighbourhood: Neighbourhood,
+        (row, column, value): ExpectedCell,
+        expected: Vec<Coordinate>,
+    ) {
         // Given
         let input: Arr2d<TestBool> = Arr2d::from_str(
             r#"
@@ -432,7 +843,7 @@ mod tests {
         .expect("Arr2d should have parsed test input");
 
         // When
-        let result: Vec<&Cell<TestBool>> = match input.flood_fill(row, column) {
+        let result: Vec<Cell<TestBool>> = match input.flood_fill(row, column, &neighbourhood) {
             Ok(i) => i.collect(),
             _ => panic!("Could not flood fill"),
         };
@@ -442,7 +853,101 @@ mod tests {
         assert_cells(&result, expected_cells);
     }
 
-    fn assert_cells(result: &Vec<&Cell<TestBool>>, expected: Vec<ExpectedCell>) {
+    #[test_case(Edges::Torus, (0, 0), vec![(0, 4, false), (0, 1, true), (2, 0, true), (1, 0, false)])]
+    #[test_case(Edges::Torus, (2, 4), vec![(2, 3, true), (2, 0, true), (1, 4, true), (0, 4, false)])]
+    #[test_case(Edges::KleinBottle, (0, 0), vec![(0, 4, false), (0, 1, true), (2, 4, false), (1, 0, false)])]
+    #[test_case(Edges::KleinBottle, (2, 1), vec![(2, 0, true), (2, 2, false), (1, 1, true), (0, 3, false)])]
+    #[test_case(Edges::Padded(TestBool(true)), (0, 0), vec![(0, 1, true), (1, 0, false)])]
+    fn test_get_neighbours_edges(
+        edges: Edges<TestBool>,
+        (row, column): Coordinate,
+        expected: Vec<ExpectedCell>,
+    ) {
+        // Given
+        let input: Arr2d<TestBool> = Arr2d::from_str(
+            r#"
+            yyynn
+            nynny
+            ynnyn
+"#,
+        )
+        .expect("Arr2d should have parsed test input")
+        .with_edges(edges);
+
+        // When
+        let result: Vec<Cell<TestBool>> = input
+            .get_neighbours(row, column, &Neighbourhood::VonNeumann)
+            .collect();
+
+        // Then
+        assert_cells(&result, expected);
+    }
+
+    #[test_case(Edges::Bounded, 1)]
+    #[test_case(Edges::Torus, 3)]
+    #[test_case(Edges::Padded(TestBool(true)), 6)]
+    #[test_case(Edges::Padded(TestBool(false)), 1)]
+    fn test_neighbour_values(edges: Edges<TestBool>, expected_alive: usize) {
+        let input: Arr2d<TestBool> = Arr2d::from_str("yyn\nnnn\nnyy")
+            .expect("Arr2d should have parsed test input")
+            .with_edges(edges);
+
+        let result = input
+            .neighbour_values(0, 0, &Neighbourhood::Moore)
+            .filter(|v| v.0)
+            .count();
+
+        assert_eq!(result, expected_alive);
+    }
+
+    #[test]
+    fn test_flood_fill_torus() {
+        // Given
+        let input: Arr2d<TestBool> = Arr2d::from_str(
+            r#"
+            yyynn
+            nynny
+            ynnyn
+"#,
+        )
+        .expect("Arr2d should have parsed test input")
+        .with_edges(Edges::Torus);
+
+        // When
+        let result: Vec<Cell<TestBool>> = input
+            .flood_fill(2, 1, &Neighbourhood::VonNeumann)
+            .expect("Could not flood fill")
+            .collect();
+
+        // Then
+        let expected = vec![(2, 1), (2, 2), (1, 2), (1, 3), (0, 3), (0, 4), (2, 4)];
+        assert_cells(&result, ex_cells_with_value(expected, false));
+    }
+
+    #[test]
+    fn test_get_perimeter_torus() {
+        // Given
+        let input: Arr2d<TestBool> = Arr2d::from_str(
+            r#"
+            nyn
+            nnn
+            nnn
+"#,
+        )
+        .expect("Arr2d should have parsed test input")
+        .with_edges(Edges::Torus);
+
+        // When
+        let result: Vec<Cell<TestBool>> = input
+            .get_perimeter(1, 1, &Neighbourhood::VonNeumann)
+            .expect("Could not get perimeter")
+            .collect();
+
+        // Then
+        assert_cells(&result, vec![(0, 1, true)]);
+    }
+
+    fn assert_cells(result: &[Cell<TestBool>], expected: Vec<ExpectedCell>) {
         assert_eq!(
             expected.len(),
             result.len(),
@@ -451,12 +956,205 @@ mod tests {
         for (ex_row, ex_column, value) in expected {
             let expected_cell = Cell::from(ex_row, ex_column, TestBool(value));
             assert!(
-                result.contains(&&expected_cell),
+                result.contains(&expected_cell),
                 "result {result:?} does not contain {expected_cell:?}"
             );
         }
     }
 
+    #[test]
+    fn test_try_get() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        assert_eq!(input.try_get(1, 2), Ok(&'f'));
+        assert_eq!(
+            input.try_get(0, 3),
+            Err(Arr2dError::OutOfBounds {
+                row: 0,
+                col: 3,
+                rows: 2,
+                cols: 3
+            })
+        );
+        assert!(input.try_get(2, 0).is_err());
+    }
+
+    #[test]
+    fn test_try_set() {
+        let mut input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        assert_eq!(input.try_set(0, 1, 'x'), Ok(()));
+        assert!(input.try_set(5, 1, 'x').is_err());
+        *input.get_mut(1, 0).unwrap() = 'y';
+
+        assert_eq!(input.to_str(), "axc\nyef\n");
+    }
+
+    #[test]
+    fn test_index() {
+        let mut input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        input[(1, 1)] = 'x';
+
+        assert_eq!(input[(0, 2)], 'c');
+        assert_eq!(input[(1, 1)], 'x');
+    }
+
+    #[test]
+    #[should_panic(expected = "position (0, 3) is outside of a 2x3 grid")]
+    fn test_get_does_not_wrap_onto_next_row() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        input.get(0, 3);
+    }
+
+    #[test]
+    fn test_flood_fill_out_of_bounds() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        let result = input.flood_fill(2, 0, &Neighbourhood::VonNeumann).err();
+
+        assert_eq!(
+            result,
+            Some(Arr2dError::OutOfBounds {
+                row: 2,
+                col: 0,
+                rows: 2,
+                cols: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_cell_identity_is_its_position() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+        let cell = input.get_cell(1, 2).unwrap();
+
+        assert_eq!(cell.id(), Coord::new(1, 2));
+        assert_eq!(cell, Cell::from(1, 2, 'f'));
+        assert_eq!(Cell::from(1, 2, 'f').id(), cell.id());
+        assert!(HashSet::from([Cell::from(1, 2, 'f')]).contains(&cell));
+        assert_ne!(cell, Cell::from(1, 2, 'x'));
+    }
+
+    #[test]
+    fn test_error_messages() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        let crop = input.crop(Rect::new(1, 1, 2, 2)).unwrap_err();
+        let zip = input.zip_with(&Arr2d::<char>::new(), |a, _| a).unwrap_err();
+
+        assert_eq!(
+            crop.to_string(),
+            "2x2 region at (1, 1) extends beyond a 2x3 grid"
+        );
+        assert_eq!(zip.to_string(), "expected a 2x3 grid, found 0x0");
+    }
+
+    #[test]
+    fn test_iter_mut() {
+        let mut input: Arr2d<TestBool> = Arr2d::from_str("yn\nny").unwrap();
+
+        input.iter_mut().for_each(|v| v.0 = !v.0);
+
+        assert_eq!(input, Arr2d::from_str("ny\nyn").unwrap());
+    }
+
+    #[test]
+    fn test_rows_iter() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        let result: Vec<String> = input.rows_iter().map(|r| r.iter().collect()).collect();
+
+        assert_eq!(result, vec!["abc", "def"]);
+    }
+
+    #[test]
+    fn test_rows_and_columns_of_zero_width_grid() {
+        let input = Arr2d::from_fn(3, 0, |_, _| 'a');
+
+        let rows: Vec<&[char]> = input.rows_iter().collect();
+
+        assert_eq!(rows, [&[] as &[char]; 3]);
+        assert_eq!(rows.len(), input.height());
+        assert_eq!(input.columns_iter().count(), input.width());
+    }
+
+    #[test]
+    fn test_columns_iter() {
+        let input: Arr2d<char> = Arr2d::from_str("abc\ndef").unwrap();
+
+        let result: Vec<String> = input.columns_iter().map(|c| c.collect()).collect();
+
+        assert_eq!(result, vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_map() {
+        let input: Arr2d<TestBool> = Arr2d::from_str("yn\nny")
+            .unwrap()
+            .with_edges(Edges::Padded(TestBool(true)));
+
+        let result: Arr2d<char> = input.map(|v| if v.0 { '#' } else { '.' });
+
+        assert_eq!(result.to_str(), "#.\n.#\n");
+        assert_eq!(result.edges(), Edges::Padded('#'));
+    }
+
+    #[test]
+    fn test_equality_ignores_edges() {
+        let bounded: Arr2d<char> = Arr2d::from_str("ab\ncd").unwrap();
+        let torus = bounded.clone().with_edges(Edges::Torus);
+
+        assert_eq!(bounded, torus);
+        assert!(HashSet::from([bounded]).contains(&torus));
+        assert_ne!(Arr2d::<char>::from_str("abcd").unwrap(), torus);
+    }
+
+    #[test]
+    fn test_zip_with() {
+        let a: Arr2d<TestBool> = Arr2d::from_str("yn\nny").unwrap();
+        let b: Arr2d<TestBool> = Arr2d::from_str("yy\nnn").unwrap();
+
+        let result = a.zip_with(&b, |a, b| TestBool(a.0 && b.0)).unwrap();
+
+        assert_eq!(result, Arr2d::from_str("yn\nnn").unwrap());
+    }
+
+    #[test]
+    fn test_stencil_into() {
+        let input: Arr2d<char> = Arr2d::from_str("#..\n.#.\n...").unwrap();
+        let mut next: Arr2d<u8> = Arr2d::from_fn(3, 3, |_, _| 0);
+
+        input
+            .stencil_into(&mut next, |grid, row, column| {
+                grid.neighbour_values(row, column, &Neighbourhood::Moore)
+                    .filter(|&v| v == '#')
+                    .count() as u8
+            })
+            .unwrap();
+
+        assert_eq!(
+            next,
+            Arr2d::from_contents(vec![vec![1, 2, 1], vec![2, 1, 1], vec![1, 1, 1]])
+        );
+        assert_eq!(
+            input.stencil_into(&mut Arr2d::<u8>::new(), |_, _, _| 0),
+            Err(Arr2dError::SizeMismatch {
+                expected: (3, 3),
+                found: (0, 0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_zip_with_mismatched_sizes() {
+        let a: Arr2d<TestBool> = Arr2d::from_str("yn\nny").unwrap();
+        let b: Arr2d<TestBool> = Arr2d::from_str("yy").unwrap();
+
+        assert!(a.zip_with(&b, |a, _| a).is_err());
+    }
+
     #[test]
     fn test_expand() {
         // Given
@@ -0,0 +1,113 @@
+use std::borrow::Cow;
+
+/// Which positions count as the neighbours of a cell, expressed as `(row, column)` offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Neighbourhood {
+    /// The four orthogonal neighbours.
+    VonNeumann,
+    /// The eight orthogonal and diagonal neighbours.
+    Moore,
+    /// Every cell within the given distance on both axes, excluding the centre.
+    MooreRadius(usize),
+    /// The six neighbours on a hexagonal grid stored as offset rows, where odd rows are shifted
+    /// half a cell to the right.
+    HexOddRows,
+    /// Any list of offsets supplied by the caller.
+    Custom(Vec<(isize, isize)>),
+}
+
+const VON_NEUMANN: [(isize, isize); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+const MOORE: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+const HEX_EVEN_ROW: [(isize, isize); 6] = [(-1, -1), (-1, 0), (0, -1), (0, 1), (1, -1), (1, 0)];
+
+const HEX_ODD_ROW: [(isize, isize); 6] = [(-1, 0), (-1, 1), (0, -1), (0, 1), (1, 0), (1, 1)];
+
+impl Neighbourhood {
+    /// The offsets to apply to a cell on the given row. Only the hexagonal layout depends on the
+    /// row, the others are the same everywhere.
+    pub fn offsets(&self, row: usize) -> Cow<'_, [(isize, isize)]> {
+        match self {
+            Neighbourhood::VonNeumann => Cow::Borrowed(&VON_NEUMANN),
+            Neighbourhood::Moore => Cow::Borrowed(&MOORE),
+            Neighbourhood::MooreRadius(radius) => {
+                let radius = *radius as isize;
+                Cow::Owned(
+                    (-radius..=radius)
+                        .flat_map(|r| (-radius..=radius).map(move |c| (r, c)))
+                        .filter(|&offset| offset != (0, 0))
+                        .collect(),
+                )
+            }
+            Neighbourhood::HexOddRows if row.is_multiple_of(2) => Cow::Borrowed(&HEX_EVEN_ROW),
+            Neighbourhood::HexOddRows => Cow::Borrowed(&HEX_ODD_ROW),
+            Neighbourhood::Custom(offsets) => Cow::Borrowed(offsets),
+        }
+    }
+
+    /// Whether every offset has its opposite, so that any cell is a neighbour of each of its
+    /// neighbours. Only a [`Neighbourhood::Custom`] list can fail this.
+    pub fn is_symmetric(&self) -> bool {
+        match self {
+            Neighbourhood::Custom(offsets) => offsets
+                .iter()
+                .all(|&(dr, dc)| offsets.contains(&(-dr, -dc))),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Neighbourhood;
+    use test_case::test_case;
+
+    #[test_case(Neighbourhood::VonNeumann, 0, 4)]
+    #[test_case(Neighbourhood::Moore, 0, 8)]
+    #[test_case(Neighbourhood::MooreRadius(1), 0, 8)]
+    #[test_case(Neighbourhood::MooreRadius(2), 0, 24)]
+    #[test_case(Neighbourhood::HexOddRows, 0, 6)]
+    #[test_case(Neighbourhood::HexOddRows, 1, 6)]
+    #[test_case(Neighbourhood::Custom(vec![(2, 1), (-2, 1)]), 0, 2)]
+    fn test_offset_count(neighbourhood: Neighbourhood, row: usize, expected: usize) {
+        assert_eq!(neighbourhood.offsets(row).len(), expected);
+    }
+
+    #[test]
+    fn test_moore_radius_one_matches_moore() {
+        let mut radius = Neighbourhood::MooreRadius(1).offsets(0).into_owned();
+        let mut moore = Neighbourhood::Moore.offsets(0).into_owned();
+        radius.sort();
+        moore.sort();
+
+        assert_eq!(radius, moore);
+    }
+
+    #[test_case(Neighbourhood::VonNeumann)]
+    #[test_case(Neighbourhood::Moore)]
+    #[test_case(Neighbourhood::MooreRadius(3))]
+    #[test_case(Neighbourhood::HexOddRows)]
+    fn test_offsets_are_symmetric(neighbourhood: Neighbourhood) {
+        assert!(neighbourhood.is_symmetric());
+        // If b is a neighbour of a, then a must be a neighbour of b
+        for row in 0..2 {
+            for &(dr, dc) in neighbourhood.offsets(row).iter() {
+                let other_row = (row as isize + dr).rem_euclid(2) as usize;
+                assert!(
+                    neighbourhood.offsets(other_row).contains(&(-dr, -dc)),
+                    "{neighbourhood:?} offset ({dr}, {dc}) from row {row} has no inverse"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crate::{Arr2d, Arr2dError, CellValue};
+use rayon::prelude::*;
+
+/// Parallel versions of whole-grid operations, splitting the work into rows spread across the
+/// rayon thread pool. Each gives exactly the same result as its serial counterpart.
+impl<T> Arr2d<T>
+where
+    T: CellValue + Send + Sync,
+{
+    /// As [`Arr2d::map`], with rows mapped in parallel.
+    pub fn par_map<U>(&self, f: impl Fn(T) -> U + Send + Sync) -> Arr2d<U>
+    where
+        U: CellValue + Send,
+    {
+        Arr2d {
+            width: self.width,
+            height: self.height,
+            contents: self
+                .contents
+                .par_chunks(self.width.max(1))
+                .flat_map_iter(|row| row.iter().map(|&v| f(v)))
+                .collect(),
+            edges: self.edges.map(&f),
+        }
+    }
+
+    /// Calls `f` with the index and values of every row, with rows handled in parallel.
+    pub fn par_for_each_row(&mut self, f: impl Fn(usize, &mut [T]) + Send + Sync) {
+        self.contents
+            .par_chunks_mut(self.width.max(1))
+            .enumerate()
+            .for_each(|(row, values)| f(row, values));
+    }
+
+    /// As [`Arr2d::stencil_into`], with the rows of `next` filled in parallel. `f` only ever
+    /// reads this grid, so no row depends on another.
+    pub fn par_stencil_into<U>(
+        &self,
+        next: &mut Arr2d<U>,
+        f: impl Fn(&Arr2d<T>, usize, usize) -> U + Send + Sync,
+    ) -> Result<(), Arr2dError>
+    where
+        U: CellValue + Send,
+    {
+        self.check_same_size(next)?;
+
+        next.contents
+            .par_chunks_mut(self.width.max(1))
+            .enumerate()
+            .for_each(|(row, values)| {
+                for (column, value) in values.iter_mut().enumerate() {
+                    *value = f(self, row, column);
+                }
+            });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{arr2d, edges, neighbourhood};
+    use crate::{Arr2d, Arr2dError, Edges, Neighbourhood};
+    use proptest::prelude::*;
+    use proptest::sample::select;
+
+    fn chars() -> impl Strategy<Value = char> + Clone {
+        select(vec!['#', '.', 'x'])
+    }
+
+    fn count_hashes(grid: &Arr2d<char>, row: usize, column: usize, nb: &Neighbourhood) -> u8 {
+        grid.neighbour_values(row, column, nb)
+            .filter(|&v| v == '#')
+            .count() as u8
+    }
+
+    proptest! {
+        #[test]
+        fn par_map_matches_map(grid in arr2d(chars(), 40, 40), edges in edges(chars())) {
+            let grid = grid.with_edges(edges);
+            let f = |v: char| v == '#';
+
+            prop_assert_eq!(grid.par_map(f), grid.map(f));
+        }
+
+        #[test]
+        fn par_for_each_row_matches_rows_iter(grid in arr2d(chars(), 40, 40)) {
+            let mut result = grid.clone();
+
+            result.par_for_each_row(|row, values| {
+                values.rotate_left(row % values.len());
+            });
+
+            for (row, (values, original)) in result.rows_iter().zip(grid.rows_iter()).enumerate() {
+                let mut expected = original.to_vec();
+                let shift = row % expected.len();
+                expected.rotate_left(shift);
+                prop_assert_eq!(values, expected.as_slice());
+            }
+        }
+
+        #[test]
+        fn par_stencil_matches_stencil(
+            grid in arr2d(chars(), 40, 40),
+            edges in edges(chars()),
+            neighbourhood in neighbourhood(),
+        ) {
+            let grid = grid.with_edges(edges);
+            let f = |g: &Arr2d<char>, r, c| count_hashes(g, r, c, &neighbourhood);
+            let mut serial = grid.map(|_| 0u8);
+            let mut parallel = grid.map(|_| 0u8);
+
+            grid.stencil_into(&mut serial, f).unwrap();
+            grid.par_stencil_into(&mut parallel, f).unwrap();
+
+            prop_assert_eq!(parallel, serial);
+        }
+    }
+
+    #[test]
+    fn test_par_stencil_size_mismatch() {
+        let grid: Arr2d<char> = Arr2d::from_fn(2, 3, |_, _| '.').with_edges(Edges::Torus);
+        let mut next: Arr2d<char> = Arr2d::from_fn(3, 2, |_, _| '.');
+
+        let result = grid.par_stencil_into(&mut next, |_, _, _| '#');
+
+        assert_eq!(
+            result,
+            Err(Arr2dError::SizeMismatch {
+                expected: (2, 3),
+                found: (3, 2)
+            })
+        );
+        assert_eq!(next, Arr2d::from_fn(3, 2, |_, _| '.'));
+    }
+}
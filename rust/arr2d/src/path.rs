@@ -0,0 +1,400 @@
+use crate::{Arr2d, Arr2dError, Cell, CellValue, Neighbourhood};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+/// A route between two cells of a grid, found by [`Arr2d::shortest_path`] or
+/// [`Arr2d::cheapest_path`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path<T: CellValue> {
+    cost: u32,
+    cells: Vec<Cell<T>>,
+}
+
+impl<T> Path<T>
+where
+    T: CellValue,
+{
+    /// The total cost of the cells entered along the path, which is the number of steps for
+    /// [`Arr2d::shortest_path`].
+    pub fn cost(&self) -> u32 {
+        self.cost
+    }
+
+    /// The cells along the path, from the start to the goal inclusive.
+    pub fn cells(&self) -> &[Cell<T>] {
+        &self.cells
+    }
+
+    /// The positions along the path, from the start to the goal inclusive.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells.iter().map(|c| (c.row, c.column))
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue,
+{
+    /// The path with the fewest steps from `from` to `to` that only enters cells for which
+    /// `passable` returns true, or `None` if the goal cannot be reached. The starting cell does
+    /// not need to be passable. Steps follow the neighbourhood and the edge policy of the grid.
+    pub fn shortest_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        neighbourhood: &Neighbourhood,
+        passable: impl Fn(T) -> bool,
+    ) -> Result<Option<Path<T>>, Arr2dError> {
+        let start = self.checked_index(from.0, from.1)?;
+        let goal = self.checked_index(to.0, to.1)?;
+
+        let mut came_from: Vec<Option<usize>> = vec![None; self.contents.len()];
+        let mut to_visit = VecDeque::from([start]);
+        came_from[start] = Some(start);
+
+        while let Some(index) = to_visit.pop_front() {
+            if index == goal {
+                let cells = self.trace_back(&came_from, goal);
+                let cost = cells.len() as u32 - 1;
+                return Ok(Some(Path { cost, cells }));
+            }
+
+            let cell = self.cell_at(index);
+            for neighbour in self.get_neighbours(cell.row, cell.column, neighbourhood) {
+                if came_from[self.index_of_cell(&neighbour)].is_none() && passable(neighbour.value)
+                {
+                    came_from[self.index_of_cell(&neighbour)] = Some(index);
+                    to_visit.push_back(self.index_of_cell(&neighbour));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The path from `from` to `to` with the lowest total cost, or `None` if the goal cannot be
+    /// reached. `cost` gives the price of entering a cell, or `None` if it cannot be entered.
+    /// A path whose total cost would not fit in a `u32` counts as unreachable.
+    pub fn cheapest_path(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        neighbourhood: &Neighbourhood,
+        cost: impl Fn(T) -> Option<u32>,
+    ) -> Result<Option<Path<T>>, Arr2dError> {
+        self.cheapest_path_with_heuristic(from, to, neighbourhood, cost, |_, _| 0)
+    }
+
+    /// A* search, as [`Arr2d::cheapest_path`] but guided by `heuristic`, an estimate of the cost
+    /// from a position to the goal. The heuristic must never overestimate the real cost,
+    /// otherwise the path found may not be the cheapest.
+    pub fn cheapest_path_with_heuristic(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        neighbourhood: &Neighbourhood,
+        cost: impl Fn(T) -> Option<u32>,
+        heuristic: impl Fn(usize, usize) -> u32,
+    ) -> Result<Option<Path<T>>, Arr2dError> {
+        let start = self.checked_index(from.0, from.1)?;
+        let goal = self.checked_index(to.0, to.1)?;
+
+        let mut came_from: Vec<Option<usize>> = vec![None; self.contents.len()];
+        let mut best: Vec<Option<u32>> = vec![None; self.contents.len()];
+        let mut to_visit = BinaryHeap::new();
+        came_from[start] = Some(start);
+        best[start] = Some(0);
+        to_visit.push(Reverse((heuristic(from.0, from.1), 0, start)));
+
+        while let Some(Reverse((_, so_far, index))) = to_visit.pop() {
+            if index == goal {
+                let cells = self.trace_back(&came_from, goal);
+                return Ok(Some(Path {
+                    cost: so_far,
+                    cells,
+                }));
+            }
+            if best[index].is_some_and(|b| b < so_far) {
+                // Already reached more cheaply
+                continue;
+            }
+
+            let cell = self.cell_at(index);
+            for neighbour in self.get_neighbours(cell.row, cell.column, neighbourhood) {
+                let Some(step) = cost(neighbour.value) else {
+                    continue;
+                };
+                let Some(total) = so_far.checked_add(step) else {
+                    continue;
+                };
+                if best[self.index_of_cell(&neighbour)].is_none_or(|b| total < b) {
+                    best[self.index_of_cell(&neighbour)] = Some(total);
+                    came_from[self.index_of_cell(&neighbour)] = Some(index);
+                    let estimate = total.saturating_add(heuristic(neighbour.row, neighbour.column));
+                    to_visit.push(Reverse((estimate, total, self.index_of_cell(&neighbour))));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The number of steps from each position to the nearest of the `sources`, moving only
+    /// through cells for which `passable` returns true. Positions that cannot be reached are
+    /// `None`. Sources are always at distance 0, passable or not.
+    pub fn distance_field(
+        &self,
+        sources: impl IntoIterator<Item = (usize, usize)>,
+        neighbourhood: &Neighbourhood,
+        passable: impl Fn(T) -> bool,
+    ) -> Result<Arr2d<Option<u32>>, Arr2dError> {
+        let mut distances: Vec<Option<u32>> = vec![None; self.contents.len()];
+        let mut to_visit = VecDeque::new();
+        for (row, column) in sources {
+            let index = self.checked_index(row, column)?;
+            if distances[index].is_none() {
+                distances[index] = Some(0);
+                to_visit.push_back(index);
+            }
+        }
+
+        while let Some(index) = to_visit.pop_front() {
+            let cell = self.cell_at(index);
+            let distance = distances[index].map(|d| d + 1);
+            for neighbour in self.get_neighbours(cell.row, cell.column, neighbourhood) {
+                if distances[self.index_of_cell(&neighbour)].is_none() && passable(neighbour.value)
+                {
+                    distances[self.index_of_cell(&neighbour)] = distance;
+                    to_visit.push_back(self.index_of_cell(&neighbour));
+                }
+            }
+        }
+
+        Ok(Arr2d {
+            width: self.width,
+            height: self.height,
+            contents: distances,
+            edges: self.edges.without_padding(),
+        })
+    }
+
+    fn trace_back(&self, came_from: &[Option<usize>], goal: usize) -> Vec<Cell<T>> {
+        let mut cells = vec![self.cell_at(goal)];
+        let mut index = goal;
+        while let Some(previous) = came_from[index].filter(|&p| p != index) {
+            cells.push(self.cell_at(previous));
+            index = previous;
+        }
+        cells.reverse();
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, Arr2dError, Edges, Neighbourhood};
+    use test_case::test_case;
+
+    const MAZE: &str = "\
+S.#...
+.##.#.
+...#..
+.#...#
+.#.#.G";
+
+    fn open(value: char) -> bool {
+        value != '#'
+    }
+
+    fn weight(value: char) -> Option<u32> {
+        match value {
+            '#' => None,
+            '~' => Some(5),
+            _ => Some(1),
+        }
+    }
+
+    #[test]
+    fn test_shortest_path_through_maze() {
+        let input = grid(MAZE);
+
+        let result = input
+            .shortest_path((0, 0), (4, 5), &Neighbourhood::VonNeumann, open)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.cost(), 9);
+        assert_eq!(
+            result.positions().collect::<Vec<_>>(),
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (2, 1),
+                (2, 2),
+                (3, 2),
+                (3, 3),
+                (3, 4),
+                (4, 4),
+                (4, 5)
+            ]
+        );
+    }
+
+    #[test_case(Neighbourhood::VonNeumann, Edges::Bounded, 6)]
+    #[test_case(Neighbourhood::Moore, Edges::Bounded, 3)]
+    #[test_case(Neighbourhood::VonNeumann, Edges::Torus, 2)]
+    #[test_case(Neighbourhood::Moore, Edges::Torus, 1)]
+    fn test_shortest_path_follows_neighbourhood_and_edges(
+        neighbourhood: Neighbourhood,
+        edges: Edges<char>,
+        expected: u32,
+    ) {
+        let input = grid("....\n....\n....\n....").with_edges(edges);
+
+        let result = input
+            .shortest_path((0, 0), (3, 3), &neighbourhood, open)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.cost(), expected);
+        assert_eq!(result.cells().len(), expected as usize + 1);
+    }
+
+    #[test]
+    fn test_shortest_path_to_self() {
+        let input = grid("#");
+
+        let result = input
+            .shortest_path((0, 0), (0, 0), &Neighbourhood::VonNeumann, open)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.cost(), 0);
+        assert_eq!(result.positions().collect::<Vec<_>>(), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn test_shortest_path_unreachable() {
+        let input = grid("..#.\n..#.");
+
+        let result = input.shortest_path((0, 0), (1, 3), &Neighbourhood::VonNeumann, open);
+
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn test_shortest_path_off_grid() {
+        let input = grid("..\n..");
+
+        let result = input.shortest_path((0, 0), (2, 0), &Neighbourhood::VonNeumann, open);
+
+        assert_eq!(
+            result,
+            Err(Arr2dError::OutOfBounds {
+                row: 2,
+                col: 0,
+                rows: 2,
+                cols: 2
+            })
+        );
+    }
+
+    #[test]
+    fn test_cheapest_path_avoids_expensive_cells() {
+        let input = grid("....\n.~~.\n....");
+
+        let result = input
+            .cheapest_path((1, 0), (1, 3), &Neighbourhood::VonNeumann, weight)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.cost(), 5);
+        assert!(result.cells().iter().all(|c| c.value() == '.'));
+    }
+
+    #[test]
+    fn test_cheapest_path_crosses_when_cheaper() {
+        let input = grid("#####\n.~~..\n#####");
+
+        let result = input
+            .cheapest_path((1, 0), (1, 4), &Neighbourhood::VonNeumann, weight)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(result.cost(), 12);
+    }
+
+    #[test]
+    fn test_cheapest_path_skips_costs_that_overflow() {
+        let input = grid("..~\n...");
+        let huge = |value: char| Some(if value == '~' { u32::MAX } else { 1 });
+
+        let next_to = input
+            .cheapest_path((0, 1), (0, 2), &Neighbourhood::VonNeumann, huge)
+            .unwrap()
+            .unwrap();
+        let too_far = input
+            .cheapest_path((0, 0), (0, 2), &Neighbourhood::VonNeumann, huge)
+            .unwrap();
+
+        assert_eq!(next_to.cost(), u32::MAX);
+        assert!(too_far.is_none());
+    }
+
+    #[test]
+    fn test_a_star_matches_dijkstra() {
+        let input = grid(MAZE);
+        let manhattan = |row: usize, column: usize| (row.abs_diff(4) + column.abs_diff(5)) as u32;
+
+        let dijkstra = input
+            .cheapest_path((0, 0), (4, 5), &Neighbourhood::VonNeumann, weight)
+            .unwrap()
+            .unwrap();
+        let a_star = input
+            .cheapest_path_with_heuristic(
+                (0, 0),
+                (4, 5),
+                &Neighbourhood::VonNeumann,
+                weight,
+                manhattan,
+            )
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(a_star.cost(), dijkstra.cost());
+        assert_eq!(a_star.cost(), 9);
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let input = grid("...#\n.#..\n....");
+
+        let result = input
+            .distance_field([(0, 0), (2, 3)], &Neighbourhood::VonNeumann, open)
+            .unwrap();
+
+        let expected = Arr2d::from_contents(vec![
+            vec![Some(0), Some(1), Some(2), None],
+            vec![Some(1), None, Some(2), Some(1)],
+            vec![Some(2), Some(2), Some(1), Some(0)],
+        ]);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_distance_field_on_torus() {
+        let input = grid(".....").with_edges(Edges::Torus);
+
+        let result = input
+            .distance_field([(0, 0)], &Neighbourhood::VonNeumann, open)
+            .unwrap();
+
+        assert_eq!(
+            result.all_cells().map(|c| c.value()).collect::<Vec<_>>(),
+            vec![Some(0), Some(1), Some(2), Some(2), Some(1)]
+        );
+        assert_eq!(result.edges(), Edges::Torus);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::{Arr2d, Cell, CellValue, Neighbourhood, Rect, grid};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Identifies a region found by [`Arr2d::regions`]. Regions are numbered from 0 in the order
+/// their first cell appears when reading the grid row by row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RegionId(pub usize);
+
+impl fmt::Display for RegionId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// A maximal group of connected cells that share the same value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Region<T: CellValue> {
+    id: RegionId,
+    value: T,
+    size: usize,
+    bounds: Rect,
+    perimeter: Vec<Cell<T>>,
+    touching: Vec<RegionId>,
+}
+
+impl<T> Region<T>
+where
+    T: CellValue,
+{
+    pub fn id(&self) -> RegionId {
+        self.id
+    }
+
+    /// The value shared by every cell in the region.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// The number of cells in the region.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The smallest rectangle holding every cell of the region. Regions that wrap around the
+    /// edges of a torus or Klein bottle may cover most of the grid.
+    pub fn bounds(&self) -> Rect {
+        self.bounds
+    }
+
+    /// The cells holding a different value that neighbour the region, in row-major order. These
+    /// are what [`Arr2d::get_perimeter`] finds when starting from any cell of the region.
+    pub fn perimeter(&self) -> &[Cell<T>] {
+        &self.perimeter
+    }
+
+    /// The regions that the perimeter cells belong to, in order.
+    pub fn touching(&self) -> &[RegionId] {
+        &self.touching
+    }
+}
+
+/// Every region of a grid, along with a grid of the same size labelling each position with the
+/// region it belongs to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regions<T: CellValue> {
+    labels: Arr2d<RegionId>,
+    regions: Vec<Region<T>>,
+}
+
+impl<T> Regions<T>
+where
+    T: CellValue,
+{
+    pub fn labels(&self) -> &Arr2d<RegionId> {
+        &self.labels
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Region<T>> {
+        self.regions.iter()
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the id did not come from these regions.
+    pub fn region(&self, id: RegionId) -> &Region<T> {
+        &self.regions[id.0]
+    }
+
+    /// The region covering the given position, or `None` if it is outside of the grid.
+    pub fn region_at(&self, row: usize, column: usize) -> Option<&Region<T>> {
+        self.labels
+            .try_get(row, column)
+            .ok()
+            .map(|&id| self.region(id))
+    }
+}
+
+/// Union-find over the flat indices of a grid.
+struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+}
+
+impl DisjointSet {
+    fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+        }
+    }
+
+    fn find(&mut self, mut index: usize) -> usize {
+        while self.parent[index] != index {
+            self.parent[index] = self.parent[self.parent[index]];
+            index = self.parent[index];
+        }
+        index
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return;
+        }
+
+        let (large, small) = if self.size[a] < self.size[b] {
+            (b, a)
+        } else {
+            (a, b)
+        };
+        self.parent[small] = large;
+        self.size[large] += self.size[small];
+    }
+}
+
+/// Grows `bounds` to cover the given position.
+fn include(bounds: Rect, row: usize, column: usize) -> Rect {
+    let top = bounds.row.min(row);
+    let left = bounds.column.min(column);
+    let bottom = (bounds.row + bounds.height).max(row + 1);
+    let right = (bounds.column + bounds.width).max(column + 1);
+    Rect::new(top, left, bottom - top, right - left)
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue,
+{
+    /// Labels every connected group of equal values in a single pass over the grid, following
+    /// the edge policy of the grid. This finds the same groups as calling [`Arr2d::flood_fill`]
+    /// from every cell, without repeating work for cells already visited.
+    ///
+    /// As with [`Arr2d::flood_fill`], two cells are only joined when each is a neighbour of the
+    /// other.
+    pub fn regions(&self, neighbourhood: &Neighbourhood) -> Regions<T> {
+        let symmetric = neighbourhood.is_symmetric();
+        let mut set = DisjointSet::new(self.contents.len());
+        for cell in self.all_cells() {
+            for neighbour in self.get_neighbours(cell.row, cell.column, neighbourhood) {
+                let from = (cell.row as isize, cell.column as isize);
+                let to = (neighbour.row as isize, neighbour.column as isize);
+                if neighbour.value == cell.value
+                    && (symmetric || grid::links_back(self, from, to, neighbourhood))
+                {
+                    set.union(self.index_of_cell(&cell), self.index_of_cell(&neighbour));
+                }
+            }
+        }
+
+        // Number the roots in the order they are first seen
+        let mut root_ids = vec![None; self.contents.len()];
+        let mut regions: Vec<Region<T>> = Vec::new();
+        let mut labels = Vec::with_capacity(self.contents.len());
+        for cell in self.all_cells() {
+            let root = set.find(self.index_of_cell(&cell));
+            let id = *root_ids[root].get_or_insert_with(|| {
+                regions.push(Region {
+                    id: RegionId(regions.len()),
+                    value: cell.value,
+                    size: 0,
+                    bounds: Rect::new(cell.row, cell.column, 1, 1),
+                    perimeter: Vec::new(),
+                    touching: Vec::new(),
+                });
+                RegionId(regions.len() - 1)
+            });
+
+            let region = &mut regions[id.0];
+            region.size += 1;
+            region.bounds = include(region.bounds, cell.row, cell.column);
+            labels.push(id);
+        }
+
+        let labels = Arr2d {
+            width: self.width,
+            height: self.height,
+            contents: labels,
+            edges: self.edges.without_padding(),
+        };
+
+        let mut perimeters = vec![BTreeSet::new(); regions.len()];
+        for cell in self.all_cells() {
+            let id = labels.contents[self.index_of_cell(&cell)];
+            for neighbour in self.get_neighbours(cell.row, cell.column, neighbourhood) {
+                if neighbour.value != cell.value {
+                    perimeters[id.0].insert(self.index_of_cell(&neighbour));
+                }
+            }
+        }
+
+        for (region, perimeter) in regions.iter_mut().zip(perimeters) {
+            region.perimeter = perimeter.iter().map(|&i| self.cell_at(i)).collect();
+            let touching: BTreeSet<RegionId> =
+                perimeter.iter().map(|&i| labels.contents[i]).collect();
+            region.touching = touching.into_iter().collect();
+        }
+
+        Regions { labels, regions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegionId;
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, Coord, Edges, Neighbourhood, Rect};
+    use std::collections::HashSet;
+    use test_case::test_case;
+
+    fn labels(as_str: &str) -> Arr2d<RegionId> {
+        let rows: Vec<Vec<RegionId>> = as_str
+            .lines()
+            .map(|row| {
+                row.chars()
+                    .map(|c| RegionId(c.to_digit(10).unwrap() as usize))
+                    .collect()
+            })
+            .collect();
+        Arr2d::from_contents(rows)
+    }
+
+    #[test_case("aab-\nbabb\nbaab", Neighbourhood::VonNeumann, "0012\n3011\n3001")]
+    #[test_case("ab\nba", Neighbourhood::VonNeumann, "01\n23")]
+    #[test_case("ab\nba", Neighbourhood::Moore, "01\n10")]
+    fn test_labels(input: &str, neighbourhood: Neighbourhood, expected: &str) {
+        let input = grid(input);
+
+        let result = input.regions(&neighbourhood);
+
+        assert_eq!(result.labels(), &labels(expected));
+    }
+
+    #[test]
+    fn test_labels_on_torus() {
+        let input = grid("a-a\n---\na-a").with_edges(Edges::Torus);
+
+        let result = input.regions(&Neighbourhood::VonNeumann);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result.labels(),
+            &labels("010\n111\n010").with_edges(Edges::Torus)
+        );
+    }
+
+    #[test]
+    fn test_region_stats() {
+        let input = grid("----\n-xx-\n--x-\n----\nx---");
+
+        let result = input.regions(&Neighbourhood::VonNeumann);
+        let region = result.region_at(1, 2).unwrap();
+
+        assert_eq!(region.value(), 'x');
+        assert_eq!(region.size(), 3);
+        assert_eq!(region.bounds(), Rect::new(1, 1, 2, 2));
+        assert_eq!(region.touching(), &[RegionId(0)]);
+        assert_eq!(
+            region
+                .perimeter()
+                .iter()
+                .map(|c| (c.row(), c.column()))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (0, 2), (1, 0), (1, 3), (2, 1), (2, 3), (3, 2)]
+        );
+
+        let background = result.region(RegionId(0));
+        assert_eq!(background.size(), 16);
+        assert_eq!(background.bounds(), Rect::new(0, 0, 5, 4));
+        assert_eq!(background.touching(), &[RegionId(1), RegionId(2)]);
+    }
+
+    #[test_case(Neighbourhood::VonNeumann, Edges::Bounded)]
+    #[test_case(Neighbourhood::Moore, Edges::Bounded)]
+    #[test_case(Neighbourhood::HexOddRows, Edges::Bounded)]
+    #[test_case(Neighbourhood::VonNeumann, Edges::Torus)]
+    #[test_case(Neighbourhood::Moore, Edges::KleinBottle)]
+    #[test_case(Neighbourhood::Custom(vec![(1, 2), (-1, -2), (2, -1), (-2, 1)]), Edges::Bounded)]
+    #[test_case(Neighbourhood::Custom(vec![(0, 1), (1, 0), (0, -1)]), Edges::Bounded)]
+    #[test_case(Neighbourhood::Custom(vec![(0, 1), (1, 1), (-1, -1)]), Edges::Torus)]
+    fn test_regions_match_flood_fill(neighbourhood: Neighbourhood, edges: Edges<char>) {
+        let input = grid("aab-\nbabb\nbaab\n-b-a").with_edges(edges);
+
+        let result = input.regions(&neighbourhood);
+
+        for cell in input.all_cells() {
+            let region = result.region_at(cell.row(), cell.column()).unwrap();
+            let filled: HashSet<Coord> = input
+                .flood_fill(cell.row(), cell.column(), &neighbourhood)
+                .unwrap()
+                .map(|c| c.id())
+                .collect();
+            let labelled: HashSet<Coord> = input
+                .all_cells()
+                .filter(|c| result.labels()[(c.row(), c.column())] == region.id())
+                .map(|c| c.id())
+                .collect();
+            let perimeter: HashSet<Coord> = input
+                .get_perimeter(cell.row(), cell.column(), &neighbourhood)
+                .unwrap()
+                .map(|c| c.id())
+                .collect();
+
+            assert_eq!(filled, labelled);
+            assert_eq!(filled.len(), region.size());
+            assert_eq!(
+                perimeter,
+                region.perimeter().iter().map(|c| c.id()).collect()
+            );
+        }
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        let input: Arr2d<char> = Arr2d::new();
+
+        let result = input.regions(&Neighbourhood::Moore);
+
+        assert!(result.is_empty());
+        assert_eq!(result.region_at(0, 0), None);
+    }
+}
@@ -0,0 +1,419 @@
+use crate::{Arr2d, CellCodec, CellValue, CharCodec, Edges, ParseError};
+
+/// Body lines are wrapped at this length, as in the RLE files used for Life patterns.
+const LINE_LENGTH: usize = 70;
+
+/// A run-length encoded text layout for grids, as used for Life patterns. A header line gives
+/// the size, `x = 3, y = 2`, followed by runs of a count and a cell token, with `$` ending a
+/// row and `!` ending the grid. Counts of 1 are left out, so a glider is `bo$2bo$3o!`.
+///
+/// With a background value, runs of it at the end of a row and rows holding nothing else are
+/// left out, and are filled back in when parsing. Lines starting with `#` before the header are
+/// comments. Tokens must not start with a digit or contain `$`, `!` or whitespace.
+#[derive(Debug, Clone)]
+pub struct RleFormat<C, T> {
+    codec: C,
+    background: Option<T>,
+}
+
+impl<T> Default for RleFormat<CharCodec, T>
+where
+    T: CellValue + TryFrom<char> + Into<char>,
+{
+    fn default() -> Self {
+        RleFormat::new(CharCodec)
+    }
+}
+
+/// Whether text looks like a run-length encoded grid rather than one row per line, judging by
+/// its first line that is not a comment.
+pub fn is_rle(as_str: &str) -> bool {
+    as_str
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .is_some_and(|line| {
+            line.strip_prefix('x')
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+        })
+}
+
+impl<C, T> RleFormat<C, T>
+where
+    T: CellValue,
+    C: CellCodec<T>,
+{
+    pub fn new(codec: C) -> RleFormat<C, T> {
+        RleFormat {
+            codec,
+            background: None,
+        }
+    }
+
+    /// A value that can be left out at the end of rows, such as dead cells in Life.
+    pub fn with_background(mut self, background: T) -> RleFormat<C, T> {
+        self.background = Some(background);
+        self
+    }
+
+    pub fn format(&self, grid: &Arr2d<T>) -> String {
+        let mut items: Vec<String> = Vec::new();
+        let mut row_ends = 0;
+        for (row, values) in grid.rows_iter().enumerate() {
+            let mut runs: Vec<(usize, T)> = Vec::new();
+            for &value in values {
+                match runs.last_mut() {
+                    Some((count, last)) if *last == value => *count += 1,
+                    _ => runs.push((1, value)),
+                }
+            }
+            if runs.last().map(|&(_, v)| Some(v)) == Some(self.background) {
+                runs.pop();
+            }
+
+            if row > 0 {
+                row_ends += 1;
+            }
+            if runs.is_empty() {
+                continue;
+            }
+            if row_ends > 0 {
+                items.push(Self::run(row_ends, "$"));
+                row_ends = 0;
+            }
+            items.extend(
+                runs.iter()
+                    .map(|&(count, value)| Self::run(count, &self.codec.encode(value))),
+            );
+        }
+        items.push("!".to_string());
+
+        let mut as_str = format!("x = {}, y = {}\n", grid.width, grid.height);
+        let mut line_length = 0;
+        for item in items {
+            if line_length > 0 && line_length + item.len() > LINE_LENGTH {
+                as_str.push('\n');
+                line_length = 0;
+            }
+            line_length += item.len();
+            as_str.push_str(&item);
+        }
+        as_str.push('\n');
+        as_str
+    }
+
+    pub fn parse(&self, as_str: &str) -> Result<Arr2d<T>, ParseError> {
+        self.parse_lines(
+            as_str
+                .split('\n')
+                .enumerate()
+                .map(|(i, line)| (i + 1, line)),
+        )
+    }
+
+    /// Parses a grid from lines paired with their line numbers in the original input, for
+    /// grids embedded in a larger file.
+    pub fn parse_lines<'a>(
+        &self,
+        lines: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<Arr2d<T>, ParseError> {
+        let mut lines = lines
+            .skip_while(|(_, line)| line.trim().is_empty() || line.trim_start().starts_with('#'));
+
+        let (width, height) = match lines.next() {
+            Some((line, header)) => Self::parse_header(line, header)?,
+            None => {
+                return Err(ParseError::NotEnoughLines {
+                    expected: 1,
+                    found: 0,
+                });
+            }
+        };
+
+        let token_width = self.codec.token_width().max(1);
+        // The header is not trusted to size anything before the runs bear it out
+        let mut contents: Vec<T> = Vec::new();
+        let mut row: Vec<T> = Vec::new();
+        let mut rows = 0;
+        let mut count: Option<usize> = None;
+        let mut last_position = (1, 1);
+
+        'lines: for (line, text) in lines {
+            let chars: Vec<char> = text.chars().collect();
+            let mut index = 0;
+            while index < chars.len() {
+                let c = chars[index];
+                let column = index + 1;
+                last_position = (line, column);
+                match c {
+                    c if c.is_whitespace() => {}
+                    '0'..='9' => {
+                        let digit = c as usize - '0' as usize;
+                        count = count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|count| count.checked_add(digit));
+                        if count.is_none() {
+                            return Err(ParseError::InvalidValue {
+                                line,
+                                found: text.trim().to_string(),
+                            });
+                        }
+                    }
+                    '$' | '!' => {
+                        let ends = if c == '$' {
+                            count.take().unwrap_or(1)
+                        } else {
+                            1
+                        };
+                        if c == '!' && row.is_empty() {
+                            break 'lines;
+                        }
+                        for _ in 0..ends {
+                            if rows == height {
+                                return Err(ParseError::InvalidToken {
+                                    line,
+                                    column,
+                                    found: c.to_string(),
+                                });
+                            }
+                            self.end_row(&mut row, &mut contents, width, (line, column))?;
+                            rows += 1;
+                        }
+                        if c == '!' {
+                            break 'lines;
+                        }
+                    }
+                    _ => {
+                        let end = (index + token_width).min(chars.len());
+                        let token: String = chars[index..end].iter().collect();
+                        let value = self
+                            .codec
+                            .decode(&token)
+                            .ok_or_else(|| Self::invalid_token(line, column, token))?;
+                        let run = count.take().unwrap_or(1);
+                        let found = row.len().saturating_add(run);
+                        if found > width {
+                            return Err(ParseError::RaggedRow {
+                                line,
+                                expected: width,
+                                found,
+                            });
+                        }
+                        row.extend(std::iter::repeat_n(value, run));
+                        index = end;
+                        continue;
+                    }
+                }
+                index += 1;
+            }
+        }
+
+        if !row.is_empty() && rows < height {
+            self.end_row(&mut row, &mut contents, width, last_position)?;
+            rows += 1;
+        }
+        while rows < height {
+            match self.background {
+                Some(background) => contents.extend(std::iter::repeat_n(background, width)),
+                None => {
+                    return Err(ParseError::NotEnoughLines {
+                        expected: height,
+                        found: rows,
+                    });
+                }
+            }
+            rows += 1;
+        }
+
+        Ok(Arr2d {
+            width,
+            height,
+            contents,
+            edges: Edges::Bounded,
+        })
+    }
+
+    /// Fills out a finished row with the background and moves it into the contents.
+    fn end_row(
+        &self,
+        row: &mut Vec<T>,
+        contents: &mut Vec<T>,
+        width: usize,
+        (line, column): (usize, usize),
+    ) -> Result<(), ParseError> {
+        if row.len() < width {
+            match self.background {
+                Some(background) => row.resize(width, background),
+                None => return Err(ParseError::NotEnoughChars { line, column }),
+            }
+        }
+        contents.append(row);
+        Ok(())
+    }
+
+    fn parse_header(line: usize, header: &str) -> Result<(usize, usize), ParseError> {
+        let invalid = || ParseError::InvalidValue {
+            line,
+            found: header.trim().to_string(),
+        };
+
+        let mut width = None;
+        let mut height = None;
+        for field in header.split(',') {
+            let (key, value) = field.split_once('=').ok_or_else(invalid)?;
+            match key.trim() {
+                "x" => width = Some(value.trim().parse().map_err(|_| invalid())?),
+                "y" => height = Some(value.trim().parse().map_err(|_| invalid())?),
+                // Other fields, such as the rule of a Life pattern, are not the grid's concern
+                _ => {}
+            }
+        }
+
+        let (width, height): (usize, usize) = width.zip(height).ok_or_else(invalid)?;
+        match width.checked_mul(height) {
+            Some(_) => Ok((width, height)),
+            None => Err(invalid()),
+        }
+    }
+
+    fn run(count: usize, token: &str) -> String {
+        match count {
+            1 => token.to_string(),
+            _ => format!("{count}{token}"),
+        }
+    }
+
+    fn invalid_token(line: usize, column: usize, token: String) -> ParseError {
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(found), None) => ParseError::InvalidCharacter {
+                line,
+                column,
+                found,
+            },
+            _ => ParseError::InvalidToken {
+                line,
+                column,
+                found: token,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RleFormat, is_rle};
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, CharCodec, ParseError, TokenCodec};
+    use test_case::test_case;
+
+    fn life() -> RleFormat<TokenCodec<bool>, bool> {
+        RleFormat::new(TokenCodec::new([(false, "b"), (true, "o")])).with_background(false)
+    }
+
+    #[test]
+    fn test_format_glider() {
+        let input = grid(".#.\n..#\n###").map(|v| v == '#');
+
+        let result = life().format(&input);
+
+        assert_eq!(result, "x = 3, y = 3\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn test_format_merges_blank_rows() {
+        let input = grid("#..\n...\n...\n..#\n...").map(|v| v == '#');
+
+        let result = life().format(&input);
+
+        assert_eq!(result, "x = 3, y = 5\no3$2bo!\n");
+        assert_eq!(life().parse(&result), Ok(input));
+    }
+
+    #[test]
+    fn test_format_without_background_keeps_every_cell() {
+        let input = grid("aab\nccc");
+
+        let result = RleFormat::default().format(&input);
+
+        assert_eq!(result, "x = 3, y = 2\n2ab$3c!\n");
+        assert_eq!(RleFormat::default().parse(&result), Ok(input));
+    }
+
+    #[test]
+    fn test_format_wraps_lines() {
+        let input = Arr2d::from_fn(3, 100, |row, column| (row + column) % 2 == 0);
+
+        let result = life().format(&input);
+
+        assert!(result.lines().all(|line| line.len() <= 70));
+        assert!(result.lines().count() > 3);
+        assert_eq!(life().parse(&result), Ok(input));
+    }
+
+    #[test]
+    fn test_round_trip_numbers() {
+        let input = Arr2d::from_contents(vec![vec![5u8, 5, 5, 7], vec![7, 7, 0, 0]]);
+        let format =
+            RleFormat::new(TokenCodec::new([(0u8, "z"), (5, "f"), (7, "s")])).with_background(0);
+
+        let result = format.parse(&format.format(&input));
+
+        assert_eq!(result, Ok(input));
+    }
+
+    #[test]
+    fn test_parse_with_comments_and_rule() {
+        let input = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbob$2b\no$3o!";
+
+        let result = life().parse(input);
+
+        assert_eq!(result, Ok(grid(".#.\n..#\n###").map(|v| v == '#')));
+    }
+
+    #[test]
+    fn test_parse_fills_missing_rows() {
+        let result = life().parse("x = 2, y = 3\no!");
+
+        assert_eq!(result, Ok(grid("#.\n..\n..").map(|v| v == '#')));
+    }
+
+    #[test_case("bo$2bo$3o!", ParseError::InvalidValue { line: 1, found: "bo$2bo$3o!".to_string() })]
+    #[test_case("x = 3, y = q\n3o!", ParseError::InvalidValue { line: 1, found: "x = 3, y = q".to_string() })]
+    #[test_case("x = 3, y = 1\n2bk!", ParseError::InvalidCharacter { line: 2, column: 3, found: 'k' })]
+    #[test_case("x = 3, y = 1\n4o!", ParseError::RaggedRow { line: 2, expected: 3, found: 4 })]
+    #[test_case("x = 4294967296, y = 4294967296\no!", ParseError::InvalidValue { line: 1, found: "x = 4294967296, y = 4294967296".to_string() })]
+    #[test_case("x = 3, y = 1\n99999999999999999999999a!", ParseError::InvalidValue { line: 2, found: "99999999999999999999999a!".to_string() })]
+    #[test_case("x = 3, y = 1\n4000000000o!", ParseError::RaggedRow { line: 2, expected: 3, found: 4_000_000_000 })]
+    #[test_case("x = 3, y = 1\no$o!", ParseError::InvalidToken { line: 2, column: 4, found: "!".to_string() })]
+    #[test_case("x = 3, y = 1\no2$!", ParseError::InvalidToken { line: 2, column: 3, found: "$".to_string() })]
+    fn test_parse_errors(input: &str, expected: ParseError) {
+        assert_eq!(life().parse(input), Err(expected));
+    }
+
+    #[test]
+    fn test_parse_without_background_needs_every_cell() {
+        let format = RleFormat::<_, char>::new(CharCodec);
+
+        assert_eq!(
+            format.parse("x = 2, y = 2\n2$"),
+            Err(ParseError::NotEnoughChars { line: 2, column: 2 })
+        );
+        assert_eq!(
+            format.parse("x = 1, y = 2\na!"),
+            Err(ParseError::NotEnoughLines {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test_case("x = 3, y = 3\nbo$2bo$3o!", true)]
+    #[test_case("#N Glider\nx=3,y=3\nbo$2bo$3o!", true)]
+    #[test_case("x--\n-x-", false)]
+    #[test_case("turn=B\nlast_move=ok", false)]
+    fn test_is_rle(input: &str, expected: bool) {
+        assert_eq!(is_rle(input), expected);
+    }
+}
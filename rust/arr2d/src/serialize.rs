@@ -0,0 +1,138 @@
+use crate::{Arr2d, CellValue, Edges};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The serialized form of an [`Arr2d`]: its dimensions and the values in row-major order. The
+/// edges are only written when they are not the default of [`Edges::Bounded`].
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Arr2d")]
+struct Repr<T> {
+    width: usize,
+    height: usize,
+    values: Vec<T>,
+    #[serde(default = "bounded", skip_serializing_if = "is_bounded")]
+    edges: Edges<T>,
+}
+
+fn bounded<T>() -> Edges<T> {
+    Edges::Bounded
+}
+
+fn is_bounded<T>(edges: &Edges<T>) -> bool {
+    matches!(edges, Edges::Bounded)
+}
+
+impl<T> Serialize for Arr2d<T>
+where
+    T: CellValue + Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Repr {
+            width: self.width,
+            height: self.height,
+            values: self.contents.clone(),
+            edges: self.edges,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Arr2d<T>
+where
+    T: CellValue + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Arr2d<T>, D::Error> {
+        let repr = Repr::<T>::deserialize(deserializer)?;
+        let Some(size) = repr.width.checked_mul(repr.height) else {
+            return Err(D::Error::custom(format!(
+                "a {}x{} grid is too large",
+                repr.height, repr.width
+            )));
+        };
+        if repr.values.len() != size {
+            return Err(D::Error::custom(format!(
+                "expected {} values for a {}x{} grid, found {}",
+                size,
+                repr.height,
+                repr.width,
+                repr.values.len()
+            )));
+        }
+
+        Ok(Arr2d {
+            width: repr.width,
+            height: repr.height,
+            contents: repr.values,
+            edges: repr.edges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, Edges};
+
+    #[test]
+    fn test_json() {
+        let input = grid("abc\ndef");
+
+        let json = serde_json::to_string(&input).unwrap();
+
+        assert_eq!(
+            json,
+            r#"{"width":3,"height":2,"values":["a","b","c","d","e","f"]}"#
+        );
+        assert_eq!(serde_json::from_str::<Arr2d<char>>(&json).unwrap(), input);
+    }
+
+    #[test]
+    fn test_json_with_edges() {
+        let input = grid("ab\ncd").with_edges(Edges::Padded('z'));
+
+        let json = serde_json::to_string(&input).unwrap();
+        let result: Arr2d<char> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(result, input);
+        assert_eq!(result.edges(), Edges::Padded('z'));
+    }
+
+    #[test]
+    fn test_json_wrong_value_count() {
+        let result =
+            serde_json::from_str::<Arr2d<char>>(r#"{"width":2,"height":2,"values":["a","b"]}"#);
+
+        let message = result.unwrap_err().to_string();
+        assert!(
+            message.contains("expected 4 values for a 2x2 grid, found 2"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_json_size_overflow() {
+        let result = serde_json::from_str::<Arr2d<char>>(
+            r#"{"width":4294967296,"height":4294967296,"values":[]}"#,
+        );
+
+        let message = result.unwrap_err().to_string();
+        assert!(
+            message.contains("a 4294967296x4294967296 grid is too large"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_ron() {
+        let input = grid("ab\ncd\nef").with_edges(Edges::Torus);
+
+        let as_ron = ron::to_string(&input).unwrap();
+        let result: Arr2d<char> = ron::from_str(&as_ron).unwrap();
+
+        assert_eq!(
+            as_ron,
+            "(width:2,height:3,values:['a','b','c','d','e','f'],edges:Torus)"
+        );
+        assert_eq!(result, input);
+    }
+}
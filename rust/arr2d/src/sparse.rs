@@ -0,0 +1,283 @@
+use crate::{Arr2d, Arr2dError, CellValue, Grid, GridMut, Neighbourhood};
+use std::collections::HashMap;
+use std::ops::Range;
+
+const CHUNK_SIZE: isize = 16;
+
+/// A block of `CHUNK_SIZE` by `CHUNK_SIZE` values, with a count of those that differ from the
+/// background so empty chunks can be dropped.
+#[derive(Debug, Clone, PartialEq)]
+struct Chunk<T> {
+    values: Vec<T>,
+    occupied: usize,
+}
+
+/// A grid without bounds, stretching forever in every direction including negative rows and
+/// columns. Every position starts out holding the background value, and storage is only used
+/// for the chunks of the plane holding something else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparseGrid<T: CellValue> {
+    background: T,
+    chunks: HashMap<(isize, isize), Chunk<T>>,
+}
+
+/// The chunk holding a position, and the index of the position within it.
+fn locate(row: isize, column: isize) -> ((isize, isize), usize) {
+    let chunk = (row.div_euclid(CHUNK_SIZE), column.div_euclid(CHUNK_SIZE));
+    let index = row.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + column.rem_euclid(CHUNK_SIZE);
+    (chunk, index as usize)
+}
+
+impl<T> SparseGrid<T>
+where
+    T: CellValue,
+{
+    pub fn new(background: T) -> SparseGrid<T> {
+        SparseGrid {
+            background,
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// Copies a grid onto the plane with its top left corner at the origin. Values matching the
+    /// background take no space.
+    pub fn from_arr2d(grid: &Arr2d<T>, background: T) -> SparseGrid<T> {
+        let mut sparse = SparseGrid::new(background);
+        for cell in grid.all_cells() {
+            sparse.set(cell.row as isize, cell.column as isize, cell.value);
+        }
+        sparse
+    }
+
+    pub fn background(&self) -> T {
+        self.background
+    }
+
+    pub fn get(&self, row: isize, column: isize) -> T {
+        let (chunk, index) = locate(row, column);
+        self.chunks
+            .get(&chunk)
+            .map_or(self.background, |c| c.values[index])
+    }
+
+    pub fn set(&mut self, row: isize, column: isize, value: T) {
+        let (key, index) = locate(row, column);
+        let background = self.background;
+        if value == background && !self.chunks.contains_key(&key) {
+            return;
+        }
+
+        let chunk = self.chunks.entry(key).or_insert_with(|| Chunk {
+            values: vec![background; (CHUNK_SIZE * CHUNK_SIZE) as usize],
+            occupied: 0,
+        });
+        let old = std::mem::replace(&mut chunk.values[index], value);
+        match (old == background, value == background) {
+            (true, false) => chunk.occupied += 1,
+            (false, true) => chunk.occupied -= 1,
+            _ => {}
+        }
+
+        if chunk.occupied == 0 {
+            self.chunks.remove(&key);
+        }
+    }
+
+    /// Resets every position to the background.
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+
+    /// The number of positions holding something other than the background.
+    pub fn len(&self) -> usize {
+        self.chunks.values().map(|c| c.occupied).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    /// The positions holding something other than the background, with their values, in no
+    /// particular order.
+    pub fn iter(&self) -> impl Iterator<Item = ((isize, isize), T)> + '_ {
+        self.chunks
+            .iter()
+            .flat_map(move |(&(chunk_row, chunk_column), chunk)| {
+                chunk
+                    .values
+                    .iter()
+                    .enumerate()
+                    .filter(move |&(_, &value)| value != self.background)
+                    .map(move |(i, &value)| {
+                        let i = i as isize;
+                        let row = chunk_row * CHUNK_SIZE + i / CHUNK_SIZE;
+                        let column = chunk_column * CHUNK_SIZE + i % CHUNK_SIZE;
+                        ((row, column), value)
+                    })
+            })
+    }
+
+    /// The smallest ranges of rows and columns covering every position that holds something
+    /// other than the background, or `None` if there are none.
+    pub fn bounds(&self) -> Option<(Range<isize>, Range<isize>)> {
+        self.iter().fold(None, |bounds, ((row, column), _)| {
+            Some(match bounds {
+                None => (row..row + 1, column..column + 1),
+                Some((rows, columns)) => (
+                    rows.start.min(row)..rows.end.max(row + 1),
+                    columns.start.min(column)..columns.end.max(column + 1),
+                ),
+            })
+        })
+    }
+
+    /// Copies out a window of the plane, with the top left corner of the window at (0, 0).
+    pub fn to_arr2d(&self, rows: Range<isize>, columns: Range<isize>) -> Arr2d<T> {
+        let height = rows.end.saturating_sub(rows.start).max(0) as usize;
+        let width = columns.end.saturating_sub(columns.start).max(0) as usize;
+        Arr2d::from_fn(height, width, |row, column| {
+            self.get(rows.start + row as isize, columns.start + column as isize)
+        })
+    }
+}
+
+impl<T> Grid for SparseGrid<T>
+where
+    T: CellValue,
+{
+    type Value = T;
+
+    fn dimensions(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    fn value_at(&self, row: isize, column: isize) -> Option<T> {
+        Some(self.get(row, column))
+    }
+
+    fn neighbours<'a>(
+        &'a self,
+        row: isize,
+        column: isize,
+        neighbourhood: &'a Neighbourhood,
+    ) -> impl Iterator<Item = (isize, isize)> + 'a {
+        let offsets = neighbourhood.offsets(row.rem_euclid(2) as usize);
+        (0..offsets.len()).map(move |i| (row + offsets[i].0, column + offsets[i].1))
+    }
+}
+
+/// Every position has a place on the plane, so this never fails.
+impl<T> GridMut for SparseGrid<T>
+where
+    T: CellValue,
+{
+    fn set_at(&mut self, row: isize, column: isize, value: T) -> Result<(), Arr2dError> {
+        self.set(row, column, value);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseGrid;
+    use crate::test_helpers::grid;
+    use crate::{Grid, Neighbourhood, flood_fill, perimeter};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_get_and_set() {
+        let mut sparse = SparseGrid::new('-');
+
+        sparse.set(-40, 7, 'x');
+        sparse.set(1_000_000, -3, 'y');
+
+        assert_eq!(sparse.get(-40, 7), 'x');
+        assert_eq!(sparse.get(1_000_000, -3), 'y');
+        assert_eq!(sparse.get(0, 0), '-');
+        assert_eq!(sparse.len(), 2);
+    }
+
+    #[test]
+    fn test_background_takes_no_space() {
+        let mut sparse = SparseGrid::new('-');
+
+        sparse.set(3, 3, '-');
+        assert!(sparse.is_empty());
+
+        sparse.set(3, 3, 'x');
+        sparse.set(3, 3, '-');
+        assert!(sparse.is_empty());
+        assert_eq!(sparse, SparseGrid::new('-'));
+    }
+
+    #[test]
+    fn test_bounds() {
+        let mut sparse = SparseGrid::new('-');
+        assert_eq!(sparse.bounds(), None);
+
+        sparse.set(-2, 5, 'x');
+        sparse.set(3, -1, 'x');
+
+        assert_eq!(sparse.bounds(), Some((-2..4, -1..6)));
+    }
+
+    #[test]
+    fn test_arr2d_round_trip() {
+        let input = grid("-x-\n--x\nxxx");
+
+        let sparse = SparseGrid::from_arr2d(&input, '-');
+
+        assert_eq!(sparse.len(), 5);
+        assert_eq!(sparse.to_arr2d(0..3, 0..3), input);
+        assert_eq!(sparse.to_arr2d(-1..1, 1..3), grid("--\nx-"));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut sparse = SparseGrid::new(0);
+        sparse.set(-17, -1, 4);
+        sparse.set(16, 16, 5);
+
+        let result: HashSet<((isize, isize), i32)> = sparse.iter().collect();
+
+        assert_eq!(result, HashSet::from([((-17, -1), 4), ((16, 16), 5)]));
+    }
+
+    #[test]
+    fn test_neighbours_have_no_edges() {
+        let sparse = SparseGrid::new('-');
+
+        let result: Vec<(isize, isize)> = sparse
+            .neighbours(0, 0, &Neighbourhood::VonNeumann)
+            .collect();
+
+        assert_eq!(result, vec![(0, -1), (0, 1), (-1, 0), (1, 0)]);
+    }
+
+    #[test]
+    fn test_flood_fill_across_chunks() {
+        let mut sparse = SparseGrid::new('-');
+        for i in -20..20 {
+            sparse.set(i, i, 'x');
+        }
+
+        let diagonal = flood_fill(&sparse, 0, 0, &Neighbourhood::Moore).count();
+        let straight = flood_fill(&sparse, 0, 0, &Neighbourhood::VonNeumann).count();
+
+        assert_eq!(diagonal, 40);
+        assert_eq!(straight, 1);
+    }
+
+    #[test]
+    fn test_perimeter() {
+        let sparse = SparseGrid::from_arr2d(&grid("xx\nx-"), '-');
+
+        let result: HashSet<(isize, isize)> =
+            perimeter(&sparse, 0, 0, &Neighbourhood::VonNeumann).collect();
+
+        assert_eq!(
+            result,
+            HashSet::from([(-1, 0), (-1, 1), (0, -1), (1, -1), (0, 2), (1, 1), (2, 0)])
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! [proptest] strategies for generating grids, for property tests in this crate and in crates
+//! built on it. Available with the `testing` feature.
+
+use crate::{Arr2d, CellValue, Edges, Neighbourhood};
+use proptest::prelude::*;
+use std::fmt::Debug;
+
+/// Grids of at least one row and column and at most the given size, holding values from
+/// `values`. Grids shrink towards fewer rows and columns.
+pub fn arr2d<T>(
+    values: impl Strategy<Value = T> + Clone,
+    max_height: usize,
+    max_width: usize,
+) -> impl Strategy<Value = Arr2d<T>>
+where
+    T: CellValue + Debug,
+{
+    (1..=max_height.max(1), 1..=max_width.max(1)).prop_flat_map(move |(height, width)| {
+        proptest::collection::vec(values.clone(), height * width).prop_map(move |contents| Arr2d {
+            width,
+            height,
+            contents,
+            edges: Edges::Bounded,
+        })
+    })
+}
+
+/// Grids as for [`arr2d`], paired with a position on the grid.
+pub fn arr2d_with_position<T>(
+    values: impl Strategy<Value = T> + Clone,
+    max_height: usize,
+    max_width: usize,
+) -> impl Strategy<Value = (Arr2d<T>, (usize, usize))>
+where
+    T: CellValue + Debug,
+{
+    arr2d(values, max_height, max_width).prop_flat_map(|grid| {
+        let position = (0..grid.height, 0..grid.width);
+        (Just(grid), position)
+    })
+}
+
+/// Every edge policy, with padding values drawn from `padding`.
+pub fn edges<T>(padding: impl Strategy<Value = T>) -> impl Strategy<Value = Edges<T>>
+where
+    T: CellValue + Debug,
+{
+    prop_oneof![
+        Just(Edges::Bounded),
+        Just(Edges::Torus),
+        Just(Edges::KleinBottle),
+        padding.prop_map(Edges::Padded),
+    ]
+}
+
+/// The built in neighbourhoods, with radii of up to 3.
+pub fn neighbourhood() -> impl Strategy<Value = Neighbourhood> {
+    prop_oneof![
+        Just(Neighbourhood::VonNeumann),
+        Just(Neighbourhood::Moore),
+        (1..=3usize).prop_map(Neighbourhood::MooreRadius),
+        Just(Neighbourhood::HexOddRows),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{arr2d, arr2d_with_position, edges, neighbourhood};
+    use crate::{Arr2d, CharCodec, RleFormat};
+    use proptest::prelude::*;
+    use proptest::sample::select;
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    /// Characters that survive a round trip through text, with few enough of them that regions
+    /// are often larger than a single cell.
+    fn chars() -> impl Strategy<Value = char> + Clone {
+        select(vec!['#', '.', 'x'])
+    }
+
+    proptest! {
+        #[test]
+        fn text_round_trip(grid in arr2d(chars(), 8, 8)) {
+            prop_assert_eq!(Arr2d::from_str(&grid.to_str()), Ok(grid));
+        }
+
+        #[test]
+        fn rle_round_trip(grid in arr2d(chars(), 12, 100), background in chars()) {
+            let plain = RleFormat::new(CharCodec);
+            let with_background = RleFormat::new(CharCodec).with_background(background);
+
+            prop_assert_eq!(plain.parse(&plain.format(&grid)), Ok(grid.clone()));
+            let encoded = with_background.format(&grid);
+            prop_assert!(encoded.lines().all(|line| line.len() <= 70));
+            prop_assert_eq!(with_background.parse(&encoded), Ok(grid));
+        }
+
+        #[test]
+        fn flood_fill_is_closed_under_equal_neighbours(
+            (grid, (row, column)) in arr2d_with_position(chars(), 8, 8),
+            edges in edges(chars()),
+            neighbourhood in neighbourhood(),
+        ) {
+            let grid = grid.with_edges(edges);
+            let start = *grid.get(row, column);
+
+            let fill: HashSet<(usize, usize)> = grid
+                .flood_fill(row, column, &neighbourhood)
+                .unwrap()
+                .map(|c| (c.row(), c.column()))
+                .collect();
+
+            prop_assert!(fill.contains(&(row, column)));
+            for &(r, c) in &fill {
+                prop_assert_eq!(*grid.get(r, c), start);
+                for neighbour in grid.get_neighbours(r, c, &neighbourhood) {
+                    if neighbour.value() == start {
+                        prop_assert!(fill.contains(&(neighbour.row(), neighbour.column())));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn perimeter_is_never_in_the_fill(
+            (grid, (row, column)) in arr2d_with_position(chars(), 8, 8),
+            edges in edges(chars()),
+            neighbourhood in neighbourhood(),
+        ) {
+            let grid = grid.with_edges(edges);
+
+            let fill: HashSet<(usize, usize)> = grid
+                .flood_fill(row, column, &neighbourhood)
+                .unwrap()
+                .map(|c| (c.row(), c.column()))
+                .collect();
+            let perimeter: Vec<(usize, usize)> = grid
+                .get_perimeter(row, column, &neighbourhood)
+                .unwrap()
+                .map(|c| (c.row(), c.column()))
+                .collect();
+
+            for position in perimeter {
+                prop_assert!(!fill.contains(&position));
+                prop_assert_ne!(*grid.get(position.0, position.1), *grid.get(row, column));
+            }
+        }
+
+        #[test]
+        fn expand_preserves_the_overlap(
+            grid in arr2d(chars(), 8, 8),
+            width in 0..10usize,
+            height in 0..10usize,
+        ) {
+            let result = grid.expand(width, height, '~');
+
+            prop_assert_eq!((result.height(), result.width()), (height, width));
+            for cell in result.all_cells() {
+                let expected = grid.try_get(cell.row(), cell.column()).copied().unwrap_or('~');
+                prop_assert_eq!(cell.value(), expected);
+            }
+        }
+
+        #[test]
+        fn transforms_compose(grid in arr2d(chars(), 8, 8)) {
+            let rotate_90 = grid.rotate_90();
+
+            prop_assert_eq!(&rotate_90.rotate_90(), &grid.rotate_180());
+            prop_assert_eq!(&rotate_90.rotate_90().rotate_90(), &grid.rotate_270());
+            prop_assert_eq!(&rotate_90.rotate_270(), &grid);
+            prop_assert_eq!(&grid.rotate_180().rotate_180(), &grid);
+            prop_assert_eq!(&grid.flip_horizontal().flip_horizontal(), &grid);
+            prop_assert_eq!(&grid.transpose().transpose(), &grid);
+            prop_assert_eq!(&rotate_90.flip_horizontal(), &grid.transpose());
+            prop_assert_eq!(&grid.rotate_180().flip_horizontal(), &grid.flip_vertical());
+            prop_assert_eq!(&grid.canonical(), &rotate_90.canonical());
+        }
+
+        #[test]
+        fn patch_from_diff_gives_the_other_grid(
+            before in arr2d(chars(), 6, 6),
+            seed in arr2d(chars(), 6, 6),
+        ) {
+            let after = seed.expand(before.width(), before.height(), '.');
+            let changes = before.diff(&after).unwrap();
+
+            let mut result = before.clone();
+            result.apply_patch(&changes).unwrap();
+
+            prop_assert_eq!(result, after);
+        }
+    }
+}
@@ -0,0 +1,462 @@
+use crate::{Arr2d, CellValue, Edges, ParseError};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Converts cell values to and from the text tokens used for them in a [`TextFormat`].
+pub trait CellCodec<T> {
+    fn encode(&self, value: T) -> String;
+
+    fn decode(&self, token: &str) -> Option<T>;
+
+    /// The number of characters in every token, used to split up rows that have no separator.
+    /// Codecs with tokens of varying width should be used with a separator.
+    fn token_width(&self) -> usize {
+        1
+    }
+}
+
+/// One character per cell, using the value's own `TryFrom<char>` and `Into<char>` conversions.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CharCodec;
+
+impl<T> CellCodec<T> for CharCodec
+where
+    T: TryFrom<char> + Into<char>,
+{
+    fn encode(&self, value: T) -> String {
+        value.into().to_string()
+    }
+
+    fn decode(&self, token: &str) -> Option<T> {
+        let mut chars = token.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => T::try_from(c).ok(),
+            _ => None,
+        }
+    }
+}
+
+/// Tokens written with `Display` and read back with `FromStr`, e.g. numbers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DisplayCodec;
+
+impl<T> CellCodec<T> for DisplayCodec
+where
+    T: Display + FromStr,
+{
+    fn encode(&self, value: T) -> String {
+        value.to_string()
+    }
+
+    fn decode(&self, token: &str) -> Option<T> {
+        token.parse().ok()
+    }
+}
+
+/// A fixed table of values and the tokens that stand for them, e.g. `".."` and `"██"`.
+#[derive(Debug, Clone)]
+pub struct TokenCodec<T> {
+    tokens: Vec<(T, String)>,
+}
+
+impl<T> TokenCodec<T>
+where
+    T: PartialEq,
+{
+    pub fn new<S: Into<String>>(tokens: impl IntoIterator<Item = (T, S)>) -> TokenCodec<T> {
+        TokenCodec {
+            tokens: tokens.into_iter().map(|(v, s)| (v, s.into())).collect(),
+        }
+    }
+}
+
+impl<T> CellCodec<T> for TokenCodec<T>
+where
+    T: PartialEq + Copy,
+{
+    /// # Panics
+    ///
+    /// Panics if the value has no token.
+    fn encode(&self, value: T) -> String {
+        match self.tokens.iter().find(|(v, _)| *v == value) {
+            Some((_, token)) => token.clone(),
+            None => panic!("TokenCodec has no token for value"),
+        }
+    }
+
+    fn decode(&self, token: &str) -> Option<T> {
+        self.tokens
+            .iter()
+            .find(|(_, t)| t == token)
+            .map(|&(value, _)| value)
+    }
+
+    fn token_width(&self) -> usize {
+        self.tokens
+            .iter()
+            .map(|(_, t)| t.chars().count())
+            .max()
+            .unwrap_or(1)
+    }
+}
+
+/// A text layout for grids: one row per line, with cells encoded by a [`CellCodec`] and joined
+/// by a separator, optionally labelled with row and column numbers. Anything written with
+/// [`TextFormat::format`] can be read back with [`TextFormat::parse`].
+///
+/// Tokens are padded to the width of the widest one so columns line up. Lines are trimmed and
+/// blank lines are skipped when parsing, and header labels are skipped rather than checked.
+#[derive(Debug, Clone)]
+pub struct TextFormat<C> {
+    codec: C,
+    separator: String,
+    row_headers: bool,
+    column_headers: bool,
+}
+
+impl Default for TextFormat<CharCodec> {
+    fn default() -> Self {
+        TextFormat::new(CharCodec)
+    }
+}
+
+struct Token {
+    column: usize,
+    text: String,
+}
+
+impl<C> TextFormat<C> {
+    pub fn new(codec: C) -> TextFormat<C> {
+        TextFormat {
+            codec,
+            separator: String::new(),
+            row_headers: false,
+            column_headers: false,
+        }
+    }
+
+    /// Text to place between the cells of a row. With no separator, rows are split into tokens
+    /// of the codec's [`CellCodec::token_width`].
+    pub fn with_separator(mut self, separator: &str) -> TextFormat<C> {
+        self.separator = separator.to_string();
+        self
+    }
+
+    /// Starts each row with its row number.
+    pub fn with_row_headers(mut self) -> TextFormat<C> {
+        self.row_headers = true;
+        self
+    }
+
+    /// Adds a first line labelling each column with its number.
+    pub fn with_column_headers(mut self) -> TextFormat<C> {
+        self.column_headers = true;
+        self
+    }
+
+    pub fn parse<T>(&self, as_str: &str) -> Result<Arr2d<T>, ParseError>
+    where
+        T: CellValue,
+        C: CellCodec<T>,
+    {
+        self.parse_lines(
+            as_str
+                .split('\n')
+                .enumerate()
+                .map(|(i, line)| (i + 1, line))
+                .filter(|(_, line)| !line.trim().is_empty()),
+        )
+    }
+
+    /// Parses one row per line, where each line is paired with its line number in the original
+    /// input. Every row must be the same width as the first, otherwise a
+    /// [`ParseError::RaggedRow`] is returned.
+    pub fn parse_lines<'a, T>(
+        &self,
+        lines: impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<Arr2d<T>, ParseError>
+    where
+        T: CellValue,
+        C: CellCodec<T>,
+    {
+        let mut contents: Vec<T> = Vec::new();
+        let mut width: Option<usize> = None;
+        let mut height = 0;
+
+        let token_width = CellCodec::<T>::token_width(&self.codec);
+        for (line, row) in lines.skip(self.column_headers as usize) {
+            let tokens = tokenise(row, &self.separator, self.row_headers, token_width);
+            for token in &tokens {
+                match self.codec.decode(&token.text) {
+                    Some(v) => contents.push(v),
+                    None => return Err(Self::invalid_token(line, token)),
+                }
+            }
+
+            let found = tokens.len();
+            match width {
+                Some(expected) if expected != found => {
+                    return Err(ParseError::RaggedRow {
+                        line,
+                        expected,
+                        found,
+                    });
+                }
+                _ => width = Some(found),
+            }
+            height += 1;
+        }
+
+        Ok(Arr2d {
+            width: width.unwrap_or(0),
+            height,
+            contents,
+            edges: Edges::Bounded,
+        })
+    }
+
+    pub fn format<T>(&self, grid: &Arr2d<T>) -> String
+    where
+        T: CellValue,
+        C: CellCodec<T>,
+    {
+        let tokens: Vec<String> = grid
+            .contents
+            .iter()
+            .map(|&v| self.codec.encode(v))
+            .collect();
+        let cell_width = tokens.iter().map(|t| t.chars().count()).max().unwrap_or(1);
+        let header_width = grid.height.saturating_sub(1).to_string().len();
+
+        let mut as_str = String::new();
+        if self.column_headers {
+            if self.row_headers {
+                as_str.push_str(&" ".repeat(header_width + 1));
+            }
+            let labels: Vec<String> = (0..grid.width)
+                .map(|column| {
+                    let label = column.to_string();
+                    let label = &label[label.len().saturating_sub(cell_width)..];
+                    format!("{label:>cell_width$}")
+                })
+                .collect();
+            as_str.push_str(&labels.join(&self.separator));
+            as_str.push('\n');
+        }
+
+        for (row, cells) in tokens.chunks(grid.width.max(1)).enumerate() {
+            if self.row_headers {
+                as_str.push_str(&format!("{row:>header_width$} "));
+            }
+            let cells: Vec<String> = cells.iter().map(|t| format!("{t:>cell_width$}")).collect();
+            as_str.push_str(&cells.join(&self.separator));
+            as_str.push('\n');
+        }
+
+        as_str
+    }
+
+    fn invalid_token(line: usize, token: &Token) -> ParseError {
+        let mut chars = token.text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(found), None) => ParseError::InvalidCharacter {
+                line,
+                column: token.column,
+                found,
+            },
+            _ => ParseError::InvalidToken {
+                line,
+                column: token.column,
+                found: token.text.clone(),
+            },
+        }
+    }
+}
+
+/// Splits a row into its tokens, recording the 1-based column at which each one starts.
+fn tokenise(row: &str, separator: &str, row_headers: bool, token_width: usize) -> Vec<Token> {
+    let chars: Vec<char> = row.chars().collect();
+    let mut start = 0;
+    let mut end = chars.len();
+    while start < end && chars[start].is_whitespace() {
+        start += 1;
+    }
+    while end > start && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    if row_headers {
+        while start < end && !chars[start].is_whitespace() {
+            start += 1;
+        }
+        while start < end && chars[start].is_whitespace() {
+            start += 1;
+        }
+    }
+
+    let trimmed = |from: usize, to: usize| {
+        let from = (from..to)
+            .find(|&i| !chars[i].is_whitespace())
+            .unwrap_or(to);
+        let to = (from..to)
+            .rev()
+            .find(|&i| !chars[i].is_whitespace())
+            .map_or(from, |i| i + 1);
+        Token {
+            column: from + 1,
+            text: chars[from..to].iter().collect(),
+        }
+    };
+
+    let mut tokens = Vec::new();
+    if separator.is_empty() {
+        let token_width = token_width.max(1);
+        let mut from = start;
+        while from < end {
+            let to = (from + token_width).min(end);
+            tokens.push(Token {
+                column: from + 1,
+                text: chars[from..to].iter().collect(),
+            });
+            from = to;
+        }
+    } else if separator.trim().is_empty() {
+        let mut from = start;
+        while from < end {
+            let to = (from..end)
+                .find(|&i| chars[i].is_whitespace())
+                .unwrap_or(end);
+            tokens.push(trimmed(from, to));
+            from = (to..end)
+                .find(|&i| !chars[i].is_whitespace())
+                .unwrap_or(end);
+        }
+    } else {
+        let separator: Vec<char> = separator.chars().collect();
+        let mut from = start;
+        let mut i = start;
+        while i < end {
+            if chars[i..end].starts_with(&separator) {
+                tokens.push(trimmed(from, i));
+                i += separator.len();
+                from = i;
+            } else {
+                i += 1;
+            }
+        }
+        if start < end {
+            tokens.push(trimmed(from, end));
+        }
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DisplayCodec, TextFormat, TokenCodec};
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, ParseError};
+    use test_case::test_case;
+
+    fn numbers() -> Arr2d<u32> {
+        Arr2d::from_contents(vec![vec![1, 10], vec![100, 7]])
+    }
+
+    fn blocks() -> TokenCodec<bool> {
+        TokenCodec::new([(false, ".."), (true, "██")])
+    }
+
+    #[test]
+    fn test_display_matches_to_str() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.to_string(), "abc\ndef\n");
+        assert_eq!(input.to_string(), input.to_str());
+    }
+
+    #[test_case(TextFormat::default(), "abc\ndef\n")]
+    #[test_case(TextFormat::default().with_separator(" "), "a b c\nd e f\n")]
+    #[test_case(TextFormat::default().with_separator(" | "), "a | b | c\nd | e | f\n"; "pipe separator")]
+    #[test_case(TextFormat::default().with_row_headers(), "0 abc\n1 def\n")]
+    #[test_case(TextFormat::default().with_column_headers(), "012\nabc\ndef\n")]
+    #[test_case(
+        TextFormat::default().with_separator(" ").with_row_headers().with_column_headers(),
+        "  0 1 2\n0 a b c\n1 d e f\n"
+    )]
+    fn test_char_formats(format: TextFormat<super::CharCodec>, expected: &str) {
+        let input = grid("abc\ndef");
+
+        let as_str = format.format(&input);
+
+        assert_eq!(as_str, expected);
+        assert_eq!(format.parse::<char>(&as_str), Ok(input));
+    }
+
+    #[test]
+    fn test_column_headers_wrap_past_ten() {
+        let input = grid("abcdefghijkl");
+        let format = TextFormat::default().with_column_headers();
+
+        let as_str = format.format(&input);
+
+        assert_eq!(as_str, "012345678901\nabcdefghijkl\n");
+        assert_eq!(format.parse::<char>(&as_str), Ok(input));
+    }
+
+    #[test]
+    fn test_numbers_are_aligned() {
+        let format = TextFormat::new(DisplayCodec).with_separator(",");
+
+        let as_str = format.format(&numbers());
+
+        assert_eq!(as_str, "  1, 10\n100,  7\n");
+        assert_eq!(format.parse::<u32>(&as_str), Ok(numbers()));
+    }
+
+    #[test]
+    fn test_numbers_with_headers() {
+        let format = TextFormat::new(DisplayCodec)
+            .with_separator(" ")
+            .with_row_headers()
+            .with_column_headers();
+
+        let as_str = format.format(&numbers());
+
+        assert_eq!(as_str, "    0   1\n0   1  10\n1 100   7\n");
+        assert_eq!(format.parse::<u32>(&as_str), Ok(numbers()));
+    }
+
+    #[test]
+    fn test_multi_character_tokens() {
+        let format = TextFormat::new(blocks());
+        let input = Arr2d::from_contents(vec![vec![false, true], vec![true, false]]);
+
+        let as_str = format.format(&input);
+
+        assert_eq!(as_str, "..██\n██..\n");
+        assert_eq!(format.parse::<bool>(&as_str), Ok(input));
+    }
+
+    #[test_case(TextFormat::new(blocks()), "..██\n..xx", ParseError::InvalidToken { line: 2, column: 3, found: "xx".to_string() })]
+    #[test_case(TextFormat::new(blocks()), "..██\n..", ParseError::RaggedRow { line: 2, expected: 2, found: 1 })]
+    #[test_case(TextFormat::new(blocks()).with_separator(" "), "..  x", ParseError::InvalidCharacter { line: 1, column: 5, found: 'x' })]
+    fn test_token_errors(format: TextFormat<TokenCodec<bool>>, input: &str, expected: ParseError) {
+        assert_eq!(format.parse::<bool>(input), Err(expected));
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        let format = TextFormat::new(DisplayCodec).with_separator(",");
+
+        let result = format.parse::<u32>("1, 2\n3, -4");
+
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidToken {
+                line: 2,
+                column: 4,
+                found: "-4".to_string()
+            })
+        );
+    }
+}
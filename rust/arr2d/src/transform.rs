@@ -0,0 +1,384 @@
+use crate::{Arr2d, Arr2dError, CellValue, Edges};
+
+/// A rectangular region of a grid, given by its top left corner and size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub row: usize,
+    pub column: usize,
+    pub height: usize,
+    pub width: usize,
+}
+
+impl Rect {
+    pub fn new(row: usize, column: usize, height: usize, width: usize) -> Rect {
+        Rect {
+            row,
+            column,
+            height,
+            width,
+        }
+    }
+
+    pub fn contains(&self, row: usize, column: usize) -> bool {
+        row.checked_sub(self.row)
+            .is_some_and(|offset| offset < self.height)
+            && column
+                .checked_sub(self.column)
+                .is_some_and(|offset| offset < self.width)
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue,
+{
+    /// Builds a grid by calling `f` with the row and column of every position.
+    pub fn from_fn(height: usize, width: usize, mut f: impl FnMut(usize, usize) -> T) -> Arr2d<T> {
+        let mut contents = Vec::with_capacity(width * height);
+        for row in 0..height {
+            for column in 0..width {
+                contents.push(f(row, column));
+            }
+        }
+
+        Arr2d {
+            width,
+            height,
+            contents,
+            edges: Edges::Bounded,
+        }
+    }
+
+    fn derive(&self, height: usize, width: usize, f: impl Fn(usize, usize) -> T) -> Arr2d<T> {
+        Arr2d::from_fn(height, width, f).with_edges(self.edges)
+    }
+
+    /// As [`Arr2d::derive`] for transforms that swap rows and columns. The twist of a Klein
+    /// bottle would then cross the left and right edges, which no [`Edges`] describes, so those
+    /// edges become [`Edges::Bounded`].
+    fn derive_swapped(&self, f: impl Fn(usize, usize) -> T) -> Arr2d<T> {
+        let edges = match self.edges {
+            Edges::KleinBottle => Edges::Bounded,
+            edges => edges,
+        };
+        Arr2d::from_fn(self.width, self.height, f).with_edges(edges)
+    }
+
+    /// Rotates the grid a quarter turn clockwise. Klein bottle edges become bounded.
+    pub fn rotate_90(&self) -> Arr2d<T> {
+        self.derive_swapped(|row, column| *self.get(self.height - 1 - column, row))
+    }
+
+    pub fn rotate_180(&self) -> Arr2d<T> {
+        self.derive(self.height, self.width, |row, column| {
+            *self.get(self.height - 1 - row, self.width - 1 - column)
+        })
+    }
+
+    /// Rotates the grid a quarter turn anticlockwise. Klein bottle edges become bounded.
+    pub fn rotate_270(&self) -> Arr2d<T> {
+        self.derive_swapped(|row, column| *self.get(column, self.width - 1 - row))
+    }
+
+    /// Mirrors the grid left to right.
+    pub fn flip_horizontal(&self) -> Arr2d<T> {
+        self.derive(self.height, self.width, |row, column| {
+            *self.get(row, self.width - 1 - column)
+        })
+    }
+
+    /// Mirrors the grid top to bottom.
+    pub fn flip_vertical(&self) -> Arr2d<T> {
+        self.derive(self.height, self.width, |row, column| {
+            *self.get(self.height - 1 - row, column)
+        })
+    }
+
+    /// Swaps rows and columns, mirroring the grid along its leading diagonal. Klein bottle edges
+    /// become bounded.
+    pub fn transpose(&self) -> Arr2d<T> {
+        self.derive_swapped(|row, column| *self.get(column, row))
+    }
+
+    /// The part of the grid covered by `rect`, which must lie entirely within the grid.
+    pub fn crop(&self, rect: Rect) -> Result<Arr2d<T>, Arr2dError> {
+        self.check_rect(rect)?;
+
+        Ok(self.derive(rect.height, rect.width, |row, column| {
+            *self.get(rect.row + row, rect.column + column)
+        }))
+    }
+
+    /// Copies `other` on top of this grid with its top left corner at the given offset. Values of
+    /// `other` for which `transparent` returns true are skipped, as is anything that falls outside
+    /// this grid.
+    pub fn paste(
+        &self,
+        other: &Arr2d<T>,
+        row: isize,
+        column: isize,
+        transparent: impl Fn(T) -> bool,
+    ) -> Arr2d<T> {
+        self.derive(self.height, self.width, |r, c| {
+            let value = *self.get(r, c);
+            let (Ok(other_r), Ok(other_c)) = (
+                usize::try_from(r as isize - row),
+                usize::try_from(c as isize - column),
+            ) else {
+                return value;
+            };
+            if other_r >= other.height || other_c >= other.width {
+                return value;
+            }
+
+            let pasted = *other.get(other_r, other_c);
+            if transparent(pasted) { value } else { pasted }
+        })
+    }
+
+    /// All eight rotations and reflections of the grid, starting with the grid itself.
+    pub fn symmetries(&self) -> [Arr2d<T>; 8] {
+        let flipped = self.flip_horizontal();
+        [
+            self.clone(),
+            self.rotate_90(),
+            self.rotate_180(),
+            self.rotate_270(),
+            flipped.rotate_90(),
+            flipped.rotate_180(),
+            flipped.rotate_270(),
+            flipped,
+        ]
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue + Ord,
+{
+    /// A representative of the grid under rotation and reflection, so that two grids are the same
+    /// pattern exactly when their canonical forms are equal. Picks the symmetry that is smallest
+    /// when comparing height, width and then each value in row order.
+    pub fn canonical(&self) -> Arr2d<T> {
+        let [first, rest @ ..] = self.symmetries();
+        rest.into_iter().fold(first, |best, candidate| {
+            if candidate.sort_key() < best.sort_key() {
+                candidate
+            } else {
+                best
+            }
+        })
+    }
+
+    fn sort_key(&self) -> (usize, usize, &[T]) {
+        (self.height, self.width, &self.contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Rect;
+    use crate::test_helpers::grid;
+    use crate::{Arr2d, Coord, Edges, Neighbourhood};
+    use test_case::test_case;
+
+    #[test]
+    fn test_rotate_90() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.rotate_90(), grid("da\neb\nfc"));
+    }
+
+    #[test]
+    fn test_rotate_180() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.rotate_180(), grid("fed\ncba"));
+    }
+
+    #[test]
+    fn test_rotate_270() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.rotate_270(), grid("cf\nbe\nad"));
+    }
+
+    #[test]
+    fn test_flips() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.flip_horizontal(), grid("cba\nfed"));
+        assert_eq!(input.flip_vertical(), grid("def\nabc"));
+    }
+
+    #[test]
+    fn test_transpose() {
+        let input = grid("abc\ndef");
+
+        assert_eq!(input.transpose(), grid("ad\nbe\ncf"));
+    }
+
+    #[test]
+    fn test_transforms_compose() {
+        let input = grid("abc\ndef\nghi\njkl");
+
+        assert_eq!(input.rotate_90().rotate_90(), input.rotate_180());
+        assert_eq!(input.rotate_90().rotate_180(), input.rotate_270());
+        assert_eq!(input.rotate_270().rotate_90(), input);
+        assert_eq!(input.transpose().transpose(), input);
+        assert_eq!(input.transpose().flip_horizontal(), input.rotate_90());
+        assert_eq!(input.flip_vertical().flip_vertical(), input);
+    }
+
+    #[test]
+    fn test_transform_ids_follow_position() {
+        let result = grid("abc\ndef").rotate_90();
+
+        let cell = result.get_cell(2, 1).unwrap();
+        assert_eq!(cell.value(), 'c');
+        assert_eq!(cell.id(), Coord::new(2, 1));
+    }
+
+    #[test]
+    fn test_transform_keeps_edges() {
+        let input = grid("ab\ncd").with_edges(Edges::Torus);
+
+        assert_eq!(input.rotate_90().edges(), Edges::Torus);
+    }
+
+    /// Each cell of a transformed grid should see the same neighbours across the twisted edges
+    /// as the cell it came from.
+    #[test_case(Arr2d::rotate_180)]
+    #[test_case(Arr2d::flip_horizontal)]
+    #[test_case(Arr2d::flip_vertical)]
+    fn test_transform_keeps_klein_bottle_neighbours(transform: fn(&Arr2d<char>) -> Arr2d<char>) {
+        let input = grid("abcd\nefgh\nijkl").with_edges(Edges::KleinBottle);
+        let neighbours = |grid: &Arr2d<char>, row, column| {
+            let mut values: Vec<char> = grid
+                .neighbour_values(row, column, &Neighbourhood::Moore)
+                .collect();
+            values.sort();
+            values
+        };
+
+        let result = transform(&input);
+
+        assert_eq!(result.edges(), Edges::KleinBottle);
+        for cell in input.all_cells() {
+            let moved = result
+                .all_cells()
+                .find(|c| c.value() == cell.value())
+                .unwrap();
+            assert_eq!(
+                neighbours(&result, moved.row(), moved.column()),
+                neighbours(&input, cell.row(), cell.column()),
+                "Neighbours of {}",
+                cell.value()
+            );
+        }
+    }
+
+    #[test_case(Arr2d::rotate_90)]
+    #[test_case(Arr2d::rotate_270)]
+    #[test_case(Arr2d::transpose)]
+    fn test_swapping_axes_bounds_klein_bottle(transform: fn(&Arr2d<char>) -> Arr2d<char>) {
+        let input = grid("abcd\nefgh\nijkl").with_edges(Edges::KleinBottle);
+
+        let result = transform(&input);
+
+        assert_eq!(result.edges(), Edges::Bounded);
+        assert_eq!(
+            result.neighbour_values(0, 0, &Neighbourhood::Moore).count(),
+            3
+        );
+    }
+
+    #[test_case(Rect::new(0, 0, 2, 2), "ab\nef")]
+    #[test_case(Rect::new(1, 1, 2, 3), "fgh\njkl")]
+    #[test_case(Rect::new(2, 3, 1, 1), "l")]
+    fn test_crop(rect: Rect, expected: &str) {
+        let input = grid("abcd\nefgh\nijkl");
+
+        assert_eq!(input.crop(rect), Ok(grid(expected)));
+    }
+
+    #[test_case(Rect::new(0, 0, 4, 1))]
+    #[test_case(Rect::new(2, 3, 1, 2))]
+    #[test_case(Rect::new(1, 0, usize::MAX, 1))]
+    #[test_case(Rect::new(0, usize::MAX, 1, 2))]
+    fn test_crop_out_of_bounds(rect: Rect) {
+        let input = grid("abcd\nefgh\nijkl");
+
+        assert!(input.crop(rect).is_err());
+    }
+
+    #[test_case(Rect::new(1, 2, 2, 3), 2, 4, true)]
+    #[test_case(Rect::new(1, 2, 2, 3), 0, 2, false)]
+    #[test_case(Rect::new(1, 2, 2, 3), 3, 2, false)]
+    #[test_case(Rect::new(1, 2, 2, 3), 1, 5, false)]
+    #[test_case(Rect::new(1, usize::MAX, 1, 2), 1, usize::MAX, true)]
+    #[test_case(Rect::new(1, usize::MAX, 1, 2), 1, 0, false)]
+    fn test_rect_contains(rect: Rect, row: usize, column: usize, expected: bool) {
+        assert_eq!(rect.contains(row, column), expected);
+    }
+
+    #[test_case(0, 0, "xyc\nz-f\nghi")]
+    #[test_case(2, 2, "abc\ndef\nghx")]
+    #[test_case(-1, -1, "-bc\ndef\nghi")]
+    fn test_paste(row: isize, column: isize, expected: &str) {
+        let base = grid("abc\ndef\nghi");
+        let pattern = grid("xy\nz-");
+
+        let result = base.paste(&pattern, row, column, |_| false);
+
+        assert_eq!(result, grid(expected));
+    }
+
+    #[test]
+    fn test_paste_transparent() {
+        let base = grid("abc\ndef\nghi");
+        let pattern = grid("xy\n-z");
+
+        let result = base.paste(&pattern, 1, 1, |v| v == '-');
+
+        assert_eq!(result, grid("abc\ndxy\nghz"));
+    }
+
+    #[test]
+    fn test_symmetries_are_distinct_for_asymmetric_grid() {
+        let input = grid("ab\ncd");
+
+        let symmetries = input.symmetries();
+
+        for (i, a) in symmetries.iter().enumerate() {
+            for b in symmetries.iter().skip(i + 1) {
+                assert_ne!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonical_is_shared_by_all_symmetries() {
+        let glider = grid("-x-\n--x\nxxx");
+        let expected = glider.canonical();
+
+        for symmetry in glider.symmetries() {
+            assert_eq!(symmetry.canonical(), expected);
+        }
+    }
+
+    #[test]
+    fn test_canonical_of_numbers() {
+        let input = Arr2d::from_fn(2, 2, |row, column| (row * 2 + column) as u32);
+
+        assert_eq!(input.rotate_90().canonical(), input);
+        assert_eq!(input.flip_vertical().canonical(), input);
+    }
+
+    #[test]
+    fn test_canonical_prefers_fewer_rows() {
+        let input = grid("a\nb\nc");
+
+        assert_eq!(input.canonical(), grid("abc"));
+    }
+}
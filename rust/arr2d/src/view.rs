@@ -0,0 +1,138 @@
+use crate::{Arr2d, Arr2dError, CellValue, Rect};
+
+/// A borrowed rectangular region of an [`Arr2d`]. Positions are relative to the top left corner
+/// of the region.
+#[derive(Debug, Clone, Copy)]
+pub struct View<'a, T: CellValue> {
+    grid: &'a Arr2d<T>,
+    rect: Rect,
+}
+
+impl<'a, T> View<'a, T>
+where
+    T: CellValue,
+{
+    /// The region of the underlying grid this view covers.
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    pub fn width(&self) -> usize {
+        self.rect.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.rect.height
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> &'a T {
+        assert!(
+            row < self.rect.height && column < self.rect.width,
+            "View position ({row}, {column}) is outside of {:?}",
+            self.rect
+        );
+        self.grid
+            .get(self.rect.row + row, self.rect.column + column)
+    }
+
+    /// The values in the view, in row-major order.
+    pub fn values(&self) -> impl Iterator<Item = T> + 'a {
+        let View { grid, rect } = *self;
+        (rect.row..rect.row + rect.height).flat_map(move |row| {
+            (rect.column..rect.column + rect.width).map(move |column| *grid.get(row, column))
+        })
+    }
+
+    /// Copies the view out into a grid of its own.
+    pub fn to_arr2d(&self) -> Arr2d<T> {
+        Arr2d::from_fn(self.rect.height, self.rect.width, |row, column| {
+            *self.get(row, column)
+        })
+    }
+}
+
+impl<T> Arr2d<T>
+where
+    T: CellValue,
+{
+    /// Borrows the region covered by `rect`, which must lie entirely within the grid.
+    pub fn view(&self, rect: Rect) -> Result<View<'_, T>, Arr2dError> {
+        self.check_rect(rect)?;
+        Ok(View { grid: self, rect })
+    }
+
+    /// Every `height` by `width` region of the grid, sliding one cell at a time across each row
+    /// and then down to the next. Windows never extend past the edges of the grid.
+    pub fn windows(&self, height: usize, width: usize) -> impl Iterator<Item = View<'_, T>> {
+        let (rows, columns) = if height == 0 || width == 0 {
+            (0, 0)
+        } else {
+            (
+                (self.height + 1).saturating_sub(height),
+                (self.width + 1).saturating_sub(width),
+            )
+        };
+
+        (0..rows).flat_map(move |row| {
+            (0..columns).map(move |column| View {
+                grid: self,
+                rect: Rect::new(row, column, height, width),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Rect;
+    use crate::test_helpers::grid;
+    use test_case::test_case;
+
+    #[test]
+    fn test_view() {
+        let input = grid("abcd\nefgh\nijkl");
+
+        let view = input.view(Rect::new(1, 1, 2, 2)).unwrap();
+
+        assert_eq!(*view.get(0, 0), 'f');
+        assert_eq!(*view.get(1, 1), 'k');
+        assert_eq!(view.values().collect::<String>(), "fgjk");
+        assert_eq!(view.to_arr2d(), grid("fg\njk"));
+    }
+
+    #[test]
+    fn test_view_out_of_bounds() {
+        let input = grid("abcd\nefgh\nijkl");
+
+        assert!(input.view(Rect::new(2, 0, 2, 1)).is_err());
+    }
+
+    #[test_case(2, 2, vec!["abef", "bcfg", "cdgh", "efij", "fgjk", "ghkl"])]
+    #[test_case(3, 4, vec!["abcdefghijkl"])]
+    #[test_case(1, 4, vec!["abcd", "efgh", "ijkl"])]
+    #[test_case(4, 1, vec![])]
+    #[test_case(0, 1, vec![])]
+    fn test_windows(height: usize, width: usize, expected: Vec<&str>) {
+        let input = grid("abcd\nefgh\nijkl");
+
+        let result: Vec<String> = input
+            .windows(height, width)
+            .map(|w| w.values().collect())
+            .collect();
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_windows_as_stencil() {
+        let input = grid("x-x\n-x-\nxxx\n--x");
+
+        // Count the live cells around the centre of each 3x3 window
+        let counts: Vec<usize> = input
+            .windows(3, 3)
+            .map(|w| w.values().filter(|&v| v == 'x').count() - (*w.get(1, 1) == 'x') as usize)
+            .collect();
+
+        assert_eq!(counts, vec![5, 4]);
+    }
+}
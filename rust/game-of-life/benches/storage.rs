@@ -0,0 +1,194 @@
+use std::hint::black_box;
+use std::str::FromStr;
+
+use arr2d::Arr2d;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use game_of_life::{Backend, GameOfLife, GolCell};
+
+const SIZES: [usize; 3] = [19, 200, 1000];
+
+/// The storage layout `Arr2d` used before it moved to a flat `Vec<T>`, kept here so the two can be
+/// compared side by side. Every cell carries its own id and position and each row is a separate
+/// allocation.
+mod nested {
+    use game_of_life::{GameOfLife, GolCell};
+
+    pub struct Cell {
+        #[allow(dead_code)]
+        pub id: u32,
+        #[allow(dead_code)]
+        pub row: usize,
+        #[allow(dead_code)]
+        pub column: usize,
+        pub value: GolCell,
+    }
+
+    pub struct Board {
+        pub contents: Vec<Vec<Cell>>,
+    }
+
+    impl Board {
+        pub fn from_fn(size: usize, f: impl Fn(usize, usize) -> GolCell) -> Board {
+            let mut id = 0;
+            Board {
+                contents: (0..size)
+                    .map(|row| {
+                        (0..size)
+                            .map(|column| {
+                                id += 1;
+                                Cell {
+                                    id,
+                                    row,
+                                    column,
+                                    value: f(row, column),
+                                }
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            }
+        }
+
+        pub fn all_cells(&self) -> impl Iterator<Item = &Cell> {
+            self.contents.iter().flat_map(|row| row.iter())
+        }
+
+        fn alive(&self, r: usize, c: usize) -> u8 {
+            (self.contents[r][c].value == GolCell::Alive) as u8
+        }
+
+        fn count_neighbours(&self, r: usize, c: usize) -> u8 {
+            let mut n = 0;
+            let top = r > 0;
+            let left = c > 0;
+            let bottom = r < self.contents.len() - 1;
+            let right = c < self.contents[r].len() - 1;
+
+            if top && left {
+                n += self.alive(r - 1, c - 1);
+            }
+            if top {
+                n += self.alive(r - 1, c);
+            }
+            if top && right {
+                n += self.alive(r - 1, c + 1);
+            }
+            if left {
+                n += self.alive(r, c - 1);
+            }
+            if right {
+                n += self.alive(r, c + 1);
+            }
+            if bottom && left {
+                n += self.alive(r + 1, c - 1);
+            }
+            if bottom {
+                n += self.alive(r + 1, c);
+            }
+            if bottom && right {
+                n += self.alive(r + 1, c + 1);
+            }
+            n
+        }
+
+        pub fn iterate(&self, next: &mut Board) {
+            for r in 0..self.contents.len() {
+                for c in 0..self.contents[r].len() {
+                    let n = self.count_neighbours(r, c);
+                    next.contents[r][c].value =
+                        GameOfLife::next_state((self.contents[r][c].value, n));
+                }
+            }
+        }
+    }
+}
+
+fn seed(row: usize, column: usize) -> GolCell {
+    if (row * 7 + column * 13).is_multiple_of(5) {
+        GolCell::Alive
+    } else {
+        GolCell::Dead
+    }
+}
+
+fn seed_str(size: usize) -> String {
+    let mut as_str = String::new();
+    for row in 0..size {
+        for column in 0..size {
+            as_str.push(seed(row, column).into());
+        }
+        as_str.push('\n');
+    }
+    as_str
+}
+
+fn bench_iteration(c: &mut Criterion) {
+    let mut group = c.benchmark_group("all_cells");
+    for size in SIZES {
+        let nested = nested::Board::from_fn(size, seed);
+        let flat: Arr2d<GolCell> = Arr2d::from_str(&seed_str(size)).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("nested", size), &nested, |b, board| {
+            b.iter(|| {
+                board
+                    .all_cells()
+                    .filter(|cell| cell.value == GolCell::Alive)
+                    .count()
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("flat", size), &flat, |b, board| {
+            b.iter(|| {
+                board
+                    .all_cells()
+                    .filter(|cell| cell.value() == GolCell::Alive)
+                    .count()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_life(c: &mut Criterion) {
+    let mut group = c.benchmark_group("iterate");
+    for size in SIZES {
+        group.bench_function(BenchmarkId::new("nested", size), |b| {
+            let mut current = nested::Board::from_fn(size, seed);
+            let mut next = nested::Board::from_fn(size, seed);
+            b.iter(|| {
+                current.iterate(&mut next);
+                std::mem::swap(&mut current, &mut next);
+                black_box(&current);
+            })
+        });
+        group.bench_function(BenchmarkId::new("flat", size), |b| {
+            let mut game = GameOfLife::from_str(&seed_str(size)).unwrap();
+            b.iter(|| {
+                game.iterate();
+                black_box(&game);
+            })
+        });
+        #[cfg(feature = "rayon")]
+        group.bench_function(BenchmarkId::new("parallel", size), |b| {
+            let mut game = GameOfLife::from_str(&seed_str(size))
+                .unwrap()
+                .with_backend(Backend::ParallelCells);
+            b.iter(|| {
+                game.iterate();
+                black_box(&game);
+            })
+        });
+        group.bench_function(BenchmarkId::new("bits", size), |b| {
+            let mut game = GameOfLife::from_str(&seed_str(size))
+                .unwrap()
+                .with_backend(Backend::Bits);
+            b.iter(|| {
+                game.iterate();
+                black_box(&game);
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_iteration, bench_life);
+criterion_main!(benches);
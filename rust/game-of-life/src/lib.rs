@@ -1,8 +1,24 @@
 use arr2d::Arr2d;
+use arr2d::BitGrid;
+use arr2d::Edges;
+use arr2d::Grid;
+use arr2d::ImageError;
+use arr2d::ImageFormat;
+use arr2d::InvalidChar;
+use arr2d::Neighbourhood;
 use arr2d::ParseError;
+use arr2d::RleFormat;
+use arr2d::SparseGrid;
+use arr2d::TokenCodec;
+use arr2d::is_rle;
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
 
 #[derive(Hash, Eq, PartialEq, Clone, Debug, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GolCell {
     Alive,
     Dead,
@@ -22,39 +38,151 @@ impl fmt::Display for GolCell {
 }
 
 impl TryFrom<char> for GolCell {
-    type Error = ParseError;
+    type Error = InvalidChar;
 
-    fn try_from(c: char) -> Result<GolCell, ParseError> {
+    fn try_from(c: char) -> Result<GolCell, InvalidChar> {
         match c {
             GameOfLife::DEAD => Ok(GolCell::Dead),
             GameOfLife::ALIVE => Ok(GolCell::Alive),
-            _ => Err(ParseError::InvalidCharacter),
+            _ => Err(InvalidChar(c)),
         }
     }
 }
 
-impl Into<char> for GolCell {
-    fn into(self) -> char {
-        match self {
+impl From<GolCell> for char {
+    fn from(value: GolCell) -> char {
+        match value {
             GolCell::Alive => GameOfLife::ALIVE,
             GolCell::Dead => GameOfLife::DEAD,
         }
     }
 }
 
+impl From<bool> for GolCell {
+    fn from(alive: bool) -> GolCell {
+        if alive { GolCell::Alive } else { GolCell::Dead }
+    }
+}
+
+impl From<GolCell> for bool {
+    fn from(value: GolCell) -> bool {
+        value == GolCell::Alive
+    }
+}
+
+/// How a [`GameOfLife`] stores its board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// One [`GolCell`] per position, stepping a position at a time.
+    #[default]
+    Cells,
+    /// One bit per position, stepping 64 positions at a time. Much faster on large boards.
+    Bits,
+    /// As [`Backend::Cells`], with the rows of each step shared across threads.
+    #[cfg(feature = "rayon")]
+    ParallelCells,
+}
+
+#[derive(Debug)]
+enum Boards {
+    /// The current board, and scratch space for the next one.
+    Cells {
+        index: usize,
+        contents: [Arr2d<GolCell>; 2],
+        #[cfg(feature = "rayon")]
+        parallel: bool,
+    },
+    Bits {
+        board: BitGrid,
+        edges: Edges<GolCell>,
+    },
+}
+
 #[derive(Debug)]
 pub struct GameOfLife {
-    index: usize,
-    contents: [Arr2d<GolCell>; 2],
+    boards: Boards,
 }
 
 impl GameOfLife {
     pub const ALIVE: char = 'x';
     pub const DEAD: char = '-';
 
+    fn from_board(board: Arr2d<GolCell>) -> GameOfLife {
+        GameOfLife {
+            boards: Boards::Cells {
+                index: 0,
+                contents: [board.clone(), board],
+                #[cfg(feature = "rayon")]
+                parallel: false,
+            },
+        }
+    }
+
+    /// Switches how the board is stored, keeping its current state and edges.
+    pub fn with_backend(self, backend: Backend) -> GameOfLife {
+        if self.backend() == backend {
+            return self;
+        }
+
+        let board = self.current_state().into_owned();
+        match backend {
+            Backend::Cells => GameOfLife::from_board(board),
+            #[cfg(feature = "rayon")]
+            Backend::ParallelCells => GameOfLife {
+                boards: Boards::Cells {
+                    index: 0,
+                    contents: [board.clone(), board],
+                    parallel: true,
+                },
+            },
+            Backend::Bits => GameOfLife {
+                boards: Boards::Bits {
+                    board: BitGrid::from_arr2d(&board.map(bool::from)),
+                    edges: board.edges(),
+                },
+            },
+        }
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self.boards {
+            #[cfg(feature = "rayon")]
+            Boards::Cells { parallel: true, .. } => Backend::ParallelCells,
+            Boards::Cells { .. } => Backend::Cells,
+            Boards::Bits { .. } => Backend::Bits,
+        }
+    }
+
+    /// Sets what lies beyond the edges of the board, e.g. [`Edges::Torus`] to let patterns wrap
+    /// around rather than die at the edge.
+    pub fn with_edges(self, edges: Edges<GolCell>) -> GameOfLife {
+        let boards = match self.boards {
+            Boards::Cells {
+                index,
+                contents: [board0, board1],
+                #[cfg(feature = "rayon")]
+                parallel,
+            } => Boards::Cells {
+                index,
+                contents: [board0.with_edges(edges), board1.with_edges(edges)],
+                #[cfg(feature = "rayon")]
+                parallel,
+            },
+            Boards::Bits { board, .. } => Boards::Bits { board, edges },
+        };
+        GameOfLife { boards }
+    }
+
     pub fn expand(&mut self, width: usize, height: usize) {
-        for c in self.contents.iter_mut() {
-            c.expand(width, height, GolCell::Dead);
+        match &mut self.boards {
+            Boards::Cells { contents, .. } => {
+                for c in contents.iter_mut() {
+                    *c = c.expand(width, height, GolCell::Dead);
+                }
+            }
+            Boards::Bits { board, .. } => {
+                *board = BitGrid::from_arr2d(&board.to_arr2d().expand(width, height, false));
+            }
         }
     }
 
@@ -68,76 +196,136 @@ impl GameOfLife {
         }
     }
 
-    pub fn from_str(as_str: &str) -> Result<GameOfLife, ParseError> {
-        let board0 = match Arr2d::from_str(as_str) {
-            Ok(b) => b,
-            Err(e) => return Err(e),
-        };
-        let board1 = match Arr2d::from_str(as_str) {
-            Ok(b) => b,
-            Err(e) => return Err(e),
-        };
-        let contents: [Arr2d<GolCell>; 2] = [board0, board1];
-        Ok(GameOfLife { index: 0, contents })
-    }
-
     pub fn iterate(&mut self) {
-        let next_index = if self.index == 0 { 1 } else { 0 };
-        for r in 0..self.contents[self.index].rows() {
-            for c in 0..self.contents[self.index].columns(r) {
-                let n = Self::count_neighbours(&self.contents[self.index], r, c);
-                self.contents[next_index].set(
-                    r,
-                    c,
-                    Self::next_state((*self.contents[self.index].get(r, c), n)),
-                );
+        match &mut self.boards {
+            #[cfg(feature = "rayon")]
+            Boards::Cells {
+                index,
+                contents,
+                parallel: true,
+            } => {
+                let (current, next) = Self::buffers(*index, contents);
+                current
+                    .par_stencil_into(next, Self::step_cell)
+                    .expect("Both boards should be the same size");
+                *index = 1 - *index;
+            }
+            Boards::Cells {
+                index, contents, ..
+            } => {
+                let (current, next) = Self::buffers(*index, contents);
+                current
+                    .stencil_into(next, Self::step_cell)
+                    .expect("Both boards should be the same size");
+                *index = 1 - *index;
+            }
+            Boards::Bits { board, edges } => {
+                *board = board.life_step(edges.map(bool::from));
             }
         }
-        self.index = next_index;
     }
 
     pub fn to_str(&self) -> String {
-        self.contents[self.index].to_str()
+        self.current_state().to_str()
     }
 
-    fn current_state(&self) -> &Arr2d<GolCell> {
-        &self.contents[self.index]
+    /// The board in the run-length encoded format used to share Life patterns, with `o` for
+    /// live cells and `b` for dead ones.
+    pub fn to_rle(&self) -> String {
+        rle_format().format(&self.current_state())
     }
 
-    fn count_neighbours(arr2d: &Arr2d<GolCell>, r: usize, c: usize) -> u8 {
-        let mut n = 0;
+    /// The board as a raw PBM image, live cells black, each drawn as a square `scale` pixels
+    /// across.
+    pub fn to_pbm(&self, scale: usize) -> Result<Vec<u8>, ImageError> {
+        ImageFormat::default()
+            .with_scale(scale)
+            .to_pbm(&self.current_state().map(bool::from))
+    }
 
-        let top = r > 0;
-        let left = c > 0;
-        let bottom = r < arr2d.rows() - 1;
-        let right = c < arr2d.columns(r) - 1;
+    /// The board as a PNG image, drawn as for [`GameOfLife::to_pbm`].
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, scale: usize) -> Result<Vec<u8>, ImageError> {
+        ImageFormat::default()
+            .with_scale(scale)
+            .to_png(&self.current_state().map(bool::from))
+    }
 
-        if top && left && GolCell::Alive == *arr2d.get(r - 1, c - 1) {
-            n += 1;
-        }
-        if top && GolCell::Alive == *arr2d.get(r - 1, c) {
-            n += 1;
-        }
-        if top && right && GolCell::Alive == *arr2d.get(r - 1, c + 1) {
-            n += 1;
-        }
-        if left && GolCell::Alive == *arr2d.get(r, c - 1) {
-            n += 1;
-        }
-        if right && GolCell::Alive == *arr2d.get(r, c + 1) {
-            n += 1;
-        }
-        if bottom && left && GolCell::Alive == *arr2d.get(r + 1, c - 1) {
-            n += 1;
-        }
-        if bottom && GolCell::Alive == *arr2d.get(r + 1, c) {
-            n += 1;
+    fn current_state(&self) -> Cow<'_, Arr2d<GolCell>> {
+        match &self.boards {
+            Boards::Cells {
+                index, contents, ..
+            } => Cow::Borrowed(&contents[*index]),
+            Boards::Bits { board, edges } => {
+                Cow::Owned(board.to_arr2d().map(GolCell::from).with_edges(*edges))
+            }
         }
-        if bottom && right && GolCell::Alive == *arr2d.get(r + 1, c + 1) {
-            n += 1;
+    }
+
+    /// The current board and the one to write the next step into.
+    fn buffers(
+        index: usize,
+        contents: &mut [Arr2d<GolCell>; 2],
+    ) -> (&Arr2d<GolCell>, &mut Arr2d<GolCell>) {
+        let [first, second] = contents;
+        if index == 0 {
+            (first, second)
+        } else {
+            (second, first)
         }
+    }
 
-        n
+    fn step_cell(board: &Arr2d<GolCell>, r: usize, c: usize) -> GolCell {
+        Self::next_state((*board.get(r, c), Self::count_neighbours(board, r, c)))
+    }
+
+    fn count_neighbours(arr2d: &Arr2d<GolCell>, r: usize, c: usize) -> u8 {
+        arr2d
+            .neighbour_values(r, c, &Neighbourhood::Moore)
+            .filter(|&n| n == GolCell::Alive)
+            .count() as u8
+    }
+}
+
+fn rle_format() -> RleFormat<TokenCodec<GolCell>, GolCell> {
+    RleFormat::new(TokenCodec::new([
+        (GolCell::Dead, "b"),
+        (GolCell::Alive, "o"),
+    ]))
+    .with_background(GolCell::Dead)
+}
+
+/// Reads a board either as one row per line or run-length encoded, depending on whether it
+/// starts with an RLE header.
+fn parse_board(as_str: &str) -> Result<Arr2d<GolCell>, ParseError> {
+    if is_rle(as_str) {
+        rle_format().parse(as_str)
+    } else {
+        Arr2d::from_str(as_str)
+    }
+}
+
+impl FromStr for GameOfLife {
+    type Err = ParseError;
+
+    fn from_str(as_str: &str) -> Result<GameOfLife, ParseError> {
+        Ok(GameOfLife::from_board(parse_board(as_str)?))
+    }
+}
+
+/// Only the current board is written, as the other is scratch space for the next iteration.
+#[cfg(feature = "serde")]
+impl serde::Serialize for GameOfLife {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.current_state().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for GameOfLife {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<GameOfLife, D::Error> {
+        let board = Arr2d::<GolCell>::deserialize(deserializer)?;
+        Ok(GameOfLife::from_board(board))
     }
 }
 
@@ -150,9 +338,73 @@ impl PartialEq for GameOfLife {
     }
 }
 
+/// Life on an infinite plane. Only live cells are stored, so patterns such as guns that keep
+/// sending out gliders can run forever without the board having to grow.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InfiniteLife {
+    board: SparseGrid<GolCell>,
+}
+
+impl InfiniteLife {
+    pub fn iterate(&mut self) {
+        // Only live cells and their neighbours can be alive in the next generation
+        let mut counts: HashMap<(isize, isize), u8> = HashMap::new();
+        for ((row, column), _) in self.board.iter() {
+            counts.entry((row, column)).or_insert(0);
+            for position in self.board.neighbours(row, column, &Neighbourhood::Moore) {
+                *counts.entry(position).or_insert(0) += 1;
+            }
+        }
+
+        let mut next = SparseGrid::new(GolCell::Dead);
+        for ((row, column), n) in counts {
+            let state = GameOfLife::next_state((self.board.get(row, column), n));
+            next.set(row, column, state);
+        }
+        self.board = next;
+    }
+
+    pub fn get(&self, row: isize, column: isize) -> GolCell {
+        self.board.get(row, column)
+    }
+
+    /// The number of live cells.
+    pub fn population(&self) -> usize {
+        self.board.len()
+    }
+
+    /// The rows and columns covering every live cell, or `None` once everything has died.
+    pub fn bounds(&self) -> Option<(Range<isize>, Range<isize>)> {
+        self.board.bounds()
+    }
+
+    /// The smallest board holding every live cell.
+    pub fn to_str(&self) -> String {
+        match self.bounds() {
+            Some((rows, columns)) => self.board.to_arr2d(rows, columns).to_str(),
+            None => String::new(),
+        }
+    }
+}
+
+/// Parses a board in the same format as [`GameOfLife`], placing its top left corner at the
+/// origin.
+impl FromStr for InfiniteLife {
+    type Err = ParseError;
+
+    fn from_str(as_str: &str) -> Result<InfiniteLife, ParseError> {
+        let board = parse_board(as_str)?;
+        Ok(InfiniteLife {
+            board: SparseGrid::from_arr2d(&board, GolCell::Dead),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arr2d::testing;
+    use proptest::prelude::*;
     use std::fs;
     use test_case::test_case;
 
@@ -162,22 +414,151 @@ mod tests {
         assert_eq!(GolCell::Dead, GameOfLife::next_state((GolCell::Alive, 4)));
     }
 
+    #[test]
+    fn test_to_pbm() {
+        let board = GameOfLife::from_str("---\nxxx\n---").unwrap();
+
+        let result = board.to_pbm(2).unwrap();
+
+        assert_eq!(
+            result,
+            [b"P4\n6 6\n".as_slice(), &[0, 0, 0xfc, 0xfc, 0, 0]].concat()
+        );
+        let read: Arr2d<bool> = ImageFormat::default().with_scale(2).parse(&result).unwrap();
+        assert_eq!(GameOfLife::from_board(read.map(GolCell::from)), board);
+    }
+
     #[test]
     fn invalid_char() {
         let result = GameOfLife::from_str("F---");
-        assert_eq!(result, Err(ParseError::InvalidCharacter));
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidCharacter {
+                line: 1,
+                column: 1,
+                found: 'F'
+            })
+        );
+    }
+
+    #[test]
+    fn ragged_rows() {
+        let result = GameOfLife::from_str("---\n--\n---");
+        assert_eq!(
+            result,
+            Err(ParseError::RaggedRow {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+
+    #[test]
+    fn rle_glider() {
+        let result = GameOfLife::from_str("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!");
+
+        assert_eq!(result, GameOfLife::from_str("-x-\n--x\nxxx"));
+        assert_eq!(result.unwrap().to_rle(), "x = 3, y = 3\nbo$2bo$3o!\n");
+    }
+
+    #[test]
+    fn rle_gun_matches_text() {
+        let text = fs::read_to_string("examples/gun.txt").unwrap();
+        let rle = fs::read_to_string("examples/gun.rle").unwrap();
+
+        let result = GameOfLife::from_str(&rle).unwrap();
+
+        assert_eq!(result, GameOfLife::from_str(&text).unwrap());
+        assert_eq!(result.to_rle(), rle);
+        assert_eq!(InfiniteLife::from_str(&rle), InfiniteLife::from_str(&text));
     }
 
     fn create_gol_from_test_file(name: &str, index: u8) -> Result<GameOfLife, ParseError> {
         let filename = format!("resources/tests/gol/{}/{}.txt", name, index);
 
-        let file_contents = &fs::read_to_string(&filename).expect(&format!(
-            "Expected to find hardcoded test resource at {}",
-            filename
-        ));
+        let file_contents = &fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Expected to find hardcoded test resource at {}", filename));
         GameOfLife::from_str(file_contents)
     }
 
+    #[test]
+    fn glider_wraps_on_torus() {
+        let glider = "-x---\n--x--\nxxx--\n-----\n-----\n";
+        let mut state = GameOfLife::from_str(glider)
+            .unwrap()
+            .with_edges(Edges::Torus);
+
+        // A glider moves one cell diagonally every 4 generations
+        for _ in 0..20 {
+            state.iterate();
+        }
+
+        assert_eq!(state.to_str(), glider);
+    }
+
+    #[test]
+    fn glider_dies_when_bounded() {
+        let glider = "-x---\n--x--\nxxx--\n-----\n-----\n";
+        let mut state = GameOfLife::from_str(glider).unwrap();
+
+        for _ in 0..20 {
+            state.iterate();
+        }
+
+        assert_ne!(state.to_str(), glider);
+    }
+
+    #[test]
+    fn padded_edges_count_as_neighbours() {
+        let mut state = GameOfLife::from_str("---\n---\n---")
+            .unwrap()
+            .with_edges(Edges::Padded(GolCell::Alive));
+
+        state.iterate();
+
+        // Corners see five live neighbours, the middle of each edge three and the centre none
+        assert_eq!(state.to_str(), "-x-\nx-x\n-x-\n");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut state = create_gol_from_test_file("toad", 1).unwrap();
+        state.iterate();
+
+        let json = serde_json::to_string(&state).unwrap();
+        let result: GameOfLife = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(result, state);
+        assert_eq!(result, create_gol_from_test_file("toad", 2).unwrap());
+    }
+
+    fn gol_cell() -> impl Strategy<Value = GolCell> + Clone {
+        prop_oneof![Just(GolCell::Alive), Just(GolCell::Dead)]
+    }
+
+    proptest! {
+        // Wide enough for rows spanning more than one word of the bit backend
+        #[test]
+        fn bit_backend_matches_cells_on_any_board(
+            board in testing::arr2d(gol_cell(), 12, 70),
+            edges in testing::edges(gol_cell()),
+        ) {
+            let mut cells = GameOfLife::from_board(board.clone()).with_edges(edges);
+            let mut bits = GameOfLife::from_board(board)
+                .with_edges(edges)
+                .with_backend(Backend::Bits);
+
+            for _ in 0..8 {
+                cells.iterate();
+                bits.iterate();
+            }
+
+            prop_assert_eq!(bits, cells);
+        }
+    }
+
     #[test_case("blinker")]
     #[test_case("toad")]
     #[test_case("beacon")]
@@ -191,4 +572,171 @@ mod tests {
 
         assert_eq!(state1, state2);
     }
+
+    #[test]
+    fn infinite_glider_keeps_moving() {
+        let glider = "-x-\n--x\nxxx\n";
+        let mut state = InfiniteLife::from_str(glider).unwrap();
+
+        // A glider moves one cell diagonally every 4 generations
+        for _ in 0..400 {
+            state.iterate();
+        }
+
+        assert_eq!(state.population(), 5);
+        assert_eq!(state.bounds(), Some((100..103, 100..103)));
+        assert_eq!(state.to_str(), glider);
+    }
+
+    #[test]
+    fn infinite_gun_keeps_firing() {
+        let gun = fs::read_to_string("examples/gun.txt").unwrap();
+        let mut state = InfiniteLife::from_str(&gun).unwrap();
+        let start = state.population();
+
+        // The gun has a period of 30 generations, sending out a glider each time
+        for _ in 0..120 {
+            state.iterate();
+        }
+
+        assert_eq!(state.population(), start + 4 * 5);
+    }
+
+    #[test]
+    fn infinite_matches_bounded_while_inside() {
+        let mut bounded = create_gol_from_test_file("toad", 1).unwrap();
+        let mut infinite =
+            InfiniteLife::from_str(&create_gol_from_test_file("toad", 1).unwrap().to_str())
+                .unwrap();
+
+        for _ in 0..3 {
+            bounded.iterate();
+            infinite.iterate();
+        }
+
+        let (rows, columns) = infinite.bounds().unwrap();
+        let expected = Arr2d::<GolCell>::from_str(&bounded.to_str()).unwrap();
+        for row in rows {
+            for column in columns.clone() {
+                assert_eq!(
+                    infinite.get(row, column),
+                    *expected.get(row as usize, column as usize)
+                );
+            }
+        }
+        assert_eq!(
+            infinite.population(),
+            expected
+                .all_cells()
+                .filter(|c| c.value() == GolCell::Alive)
+                .count()
+        );
+    }
+
+    #[test_case(Edges::Bounded)]
+    #[test_case(Edges::Torus)]
+    #[test_case(Edges::KleinBottle)]
+    #[test_case(Edges::Padded(GolCell::Alive))]
+    fn bit_backend_matches_cells(edges: Edges<GolCell>) {
+        let gun = fs::read_to_string("examples/gun.txt").unwrap();
+        let mut cells = GameOfLife::from_str(&gun).unwrap().with_edges(edges);
+        let mut bits = GameOfLife::from_str(&gun)
+            .unwrap()
+            .with_edges(edges)
+            .with_backend(Backend::Bits);
+
+        for generation in 0..60 {
+            assert_eq!(bits, cells, "generation {generation}");
+            cells.iterate();
+            bits.iterate();
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test_case(Edges::Bounded)]
+    #[test_case(Edges::Torus)]
+    #[test_case(Edges::KleinBottle)]
+    #[test_case(Edges::Padded(GolCell::Alive))]
+    fn parallel_backend_matches_cells(edges: Edges<GolCell>) {
+        let gun = fs::read_to_string("examples/gun.txt").unwrap();
+        let mut cells = GameOfLife::from_str(&gun).unwrap().with_edges(edges);
+        let mut parallel = GameOfLife::from_str(&gun)
+            .unwrap()
+            .with_backend(Backend::ParallelCells)
+            .with_edges(edges);
+
+        for generation in 0..60 {
+            assert_eq!(parallel, cells, "generation {generation}");
+            cells.iterate();
+            parallel.iterate();
+        }
+        assert_eq!(parallel.backend(), Backend::ParallelCells);
+    }
+
+    #[cfg(feature = "rayon")]
+    proptest! {
+        #[test]
+        fn parallel_backend_matches_cells_on_any_board(
+            board in testing::arr2d(gol_cell(), 40, 40),
+            edges in testing::edges(gol_cell()),
+        ) {
+            let mut cells = GameOfLife::from_board(board.clone()).with_edges(edges);
+            let mut parallel = GameOfLife::from_board(board)
+                .with_edges(edges)
+                .with_backend(Backend::ParallelCells);
+
+            for _ in 0..8 {
+                cells.iterate();
+                parallel.iterate();
+            }
+
+            prop_assert_eq!(parallel, cells);
+        }
+    }
+
+    #[test_case("blinker")]
+    #[test_case("toad")]
+    #[test_case("beacon")]
+    fn bit_backend_oscillators(name: &str) {
+        let mut state1 = create_gol_from_test_file(name, 1)
+            .unwrap()
+            .with_backend(Backend::Bits);
+        let state2 = create_gol_from_test_file(name, 2).unwrap();
+
+        state1.iterate();
+
+        assert_eq!(state1.backend(), Backend::Bits);
+        assert_eq!(state1, state2);
+    }
+
+    #[test]
+    fn switching_backend_keeps_state() {
+        let glider = "-x---\n--x--\nxxx--\n-----\n-----\n";
+        let mut state = GameOfLife::from_str(glider)
+            .unwrap()
+            .with_edges(Edges::Torus)
+            .with_backend(Backend::Bits);
+
+        for _ in 0..10 {
+            state.iterate();
+        }
+        state = state.with_backend(Backend::Cells);
+        for _ in 0..10 {
+            state.iterate();
+        }
+
+        assert_eq!(state.backend(), Backend::Cells);
+        assert_eq!(state.to_str(), glider);
+    }
+
+    #[test]
+    fn bit_backend_expand() {
+        let mut state = GameOfLife::from_str("xx\nxx")
+            .unwrap()
+            .with_backend(Backend::Bits);
+
+        state.expand(3, 3);
+
+        assert_eq!(state.to_str(), "xx-\nxx-\n---\n");
+    }
 }
@@ -1,17 +1,25 @@
 use std::env;
 use std::error::Error;
 use std::fs;
+use std::path::Path;
 use std::process;
+use std::str::FromStr;
 
-use game_of_life::GameOfLife;
+use arr2d::Edges;
+use game_of_life::{GameOfLife, InfiniteLife};
 
 use std::{io, thread, time::Duration};
 
 const FRAME_TIME: u64 = 100;
 const FRAME_COUNT: u64 = 10;
+/// The size in pixels of each cell in frames written with `--frames`.
+const FRAME_SCALE: usize = 8;
 
 struct Config {
     basefile: String,
+    wrap: bool,
+    infinite: bool,
+    frames: Option<String>,
 }
 
 impl Config {
@@ -23,7 +31,29 @@ impl Config {
             None => return Err("Did not receive a basefile"),
         };
 
-        Ok(Config { basefile })
+        let flags: Vec<String> = args.collect();
+        let wrap = flags.iter().any(|arg| arg == "--wrap");
+        let infinite = flags.iter().any(|arg| arg == "--infinite");
+        let frames = match flags.iter().position(|arg| arg == "--frames") {
+            Some(i) => match flags.get(i + 1) {
+                Some(dir) => Some(dir.clone()),
+                None => return Err("--frames needs a directory to write to"),
+            },
+            None => None,
+        };
+        if infinite && frames.is_some() {
+            return Err("--frames cannot be used with --infinite");
+        }
+        if infinite && wrap {
+            return Err("--wrap cannot be used with --infinite");
+        }
+
+        Ok(Config {
+            basefile,
+            wrap,
+            infinite,
+            frames,
+        })
     }
 }
 
@@ -31,19 +61,48 @@ fn run(config: Config) -> Result<(), Box<dyn Error>> {
     println!("Game of Life - Example {}", config.basefile);
 
     let contents =
-        fs::read_to_string(config.basefile).expect("Should have been able to read the file");
+        fs::read_to_string(&config.basefile).expect("Should have been able to read the file");
+
+    if config.infinite {
+        let mut board = InfiniteLife::from_str(&contents)?;
+        animate(|| {
+            let as_str = board.to_str();
+            board.iterate();
+            as_str
+        });
+    } else {
+        let mut board: GameOfLife = GameOfLife::from_str(&contents)?;
+        if config.wrap {
+            board = board.with_edges(Edges::Torus);
+        }
+        if let Some(dir) = &config.frames {
+            fs::create_dir_all(dir)?;
+        }
+        let mut frame = 0;
+        animate(|| {
+            if let Some(dir) = &config.frames {
+                let path = Path::new(dir).join(format!("frame-{frame:03}.pbm"));
+                let image = board
+                    .to_pbm(FRAME_SCALE)
+                    .expect("Should have been able to draw a frame");
+                fs::write(path, image).expect("Should have been able to write a frame");
+                frame += 1;
+            }
+            let as_str = board.to_str();
+            board.iterate();
+            as_str
+        });
+    }
 
-    let mut board: GameOfLife = GameOfLife::from_str(&contents).unwrap();
+    Ok(())
+}
 
+/// Prints a frame from `step` every `FRAME_TIME` milliseconds.
+fn animate(mut step: impl FnMut() -> String) {
     for _ in 0..=FRAME_COUNT {
-        let as_str = board.to_str();
-        print!("{}", as_str);
-
-        board.iterate();
+        print!("{}", step());
         thread::sleep(Duration::from_millis(FRAME_TIME));
     }
-
-    Ok(())
 }
 
 fn main() -> Result<(), io::Error> {
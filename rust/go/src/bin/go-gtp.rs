@@ -0,0 +1,6 @@
+use go::GtpEngine;
+use std::io;
+
+fn main() -> io::Result<()> {
+    GtpEngine::default().run(io::stdin().lock(), io::stdout().lock())
+}
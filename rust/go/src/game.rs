@@ -0,0 +1,559 @@
+//! A game of Go that keeps every position reached, so that moves can be taken back and played
+//! again, and other lines explored as variations.
+
+use crate::sgf::{GameInfo, GameNode, GameRecord, SgfError, SgfNode, SgfTree};
+use crate::{GoBoard, GoBoardError, GoPlayer, Move};
+use std::fmt;
+use std::str::FromStr;
+
+/// Identifies a node of a [`GoGame`]. The root, before any moves, is [`GoGame::ROOT`], and the
+/// other nodes are numbered in the order they were added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(pub usize);
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TreeNode {
+    parent: Option<NodeId>,
+    /// The variations following this node, the first of them being the main line.
+    children: Vec<NodeId>,
+    /// The index of the child that redo moves to, which is the one most recently visited.
+    selected: usize,
+    node: GameNode,
+    /// The position once the node's stones and move have been played.
+    position: GoBoard,
+}
+
+/// A game as a tree of moves. Each node holds a move and the position it leads to, and a node
+/// with more than one child branches into variations.
+///
+/// Methods taking a [`NodeId`] panic if the id did not come from the same game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GoGame {
+    pub komi: f64,
+    pub handicap: usize,
+    pub info: GameInfo,
+    nodes: Vec<TreeNode>,
+    current: NodeId,
+}
+
+impl GoGame {
+    pub const ROOT: NodeId = NodeId(0);
+
+    /// A game on an empty board of the given size.
+    pub fn new(size: usize) -> GoGame {
+        GoGame {
+            komi: 0.0,
+            handicap: 0,
+            info: GameInfo::default(),
+            nodes: vec![TreeNode {
+                parent: None,
+                children: Vec::new(),
+                selected: 0,
+                node: GameNode::default(),
+                position: GoBoard::new(size),
+            }],
+            current: Self::ROOT,
+        }
+    }
+
+    /// The position at the current node.
+    pub fn board(&self) -> &GoBoard {
+        self.position(self.current)
+    }
+
+    pub fn current(&self) -> NodeId {
+        self.current
+    }
+
+    pub fn position(&self, node: NodeId) -> &GoBoard {
+        &self.tree_node(node).position
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.tree_node(node).parent
+    }
+
+    /// The variations following a node, the first of them being the main line.
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.tree_node(node).children
+    }
+
+    /// The move that led to a node, which the root does not have.
+    pub fn node_move(&self, node: NodeId) -> Option<(GoPlayer, Move)> {
+        self.tree_node(node).node.play
+    }
+
+    pub fn comment(&self, node: NodeId) -> Option<&str> {
+        self.tree_node(node).node.comment.as_deref()
+    }
+
+    pub fn set_comment(&mut self, node: NodeId, comment: Option<String>) {
+        self.nodes[node.0].node.comment = comment;
+    }
+
+    /// The nodes from the root to the given node.
+    pub fn line(&self, node: NodeId) -> Vec<NodeId> {
+        let mut line: Vec<NodeId> =
+            std::iter::successors(Some(node), |&n| self.parent(n)).collect();
+        line.reverse();
+        line
+    }
+
+    /// The moves played from the root to the current node.
+    pub fn moves(&self) -> Vec<(GoPlayer, Move)> {
+        self.line(self.current)
+            .into_iter()
+            .filter_map(|node| self.node_move(node))
+            .collect()
+    }
+
+    /// Plays a move for whoever's turn it is and moves to it. Playing a move that already
+    /// follows the current node moves to that node rather than adding it again.
+    pub fn play(&mut self, play: Move) -> Result<NodeId, GoBoardError> {
+        self.play_as(self.board().whos_turn(), play)
+    }
+
+    /// Plays a move for the given player, whoever's turn it is, as for [`GoGame::play`].
+    pub fn play_as(&mut self, player: GoPlayer, play: Move) -> Result<NodeId, GoBoardError> {
+        let node = self.add_variation_as(self.current, player, play)?;
+        self.go_to(node);
+        Ok(node)
+    }
+
+    /// Adds a move for whoever's turn it is after the given node, without moving to it. If the
+    /// move is already there, its node is returned instead.
+    pub fn add_variation(&mut self, parent: NodeId, play: Move) -> Result<NodeId, GoBoardError> {
+        let player = self.position(parent).whos_turn();
+        self.add_variation_as(parent, player, play)
+    }
+
+    /// Moves back to the position before the current move, returning `false` at the root.
+    pub fn undo(&mut self) -> bool {
+        match self.parent(self.current) {
+            Some(parent) => {
+                self.current = parent;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves forward along the variation last visited from the current node, or the main line
+    /// if none has been, returning `false` if there are no moves after this one.
+    pub fn redo(&mut self) -> bool {
+        let node = self.tree_node(self.current);
+        match node.children.get(node.selected) {
+            Some(&child) => {
+                self.current = child;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to another variation of the current move, counting the parent's children from 0,
+    /// returning `false` if there is no such variation.
+    pub fn switch_variation(&mut self, index: usize) -> bool {
+        let sibling = self
+            .parent(self.current)
+            .and_then(|parent| self.children(parent).get(index).copied());
+        match sibling {
+            Some(node) => {
+                self.go_to(node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to any node, so that redoing from the nodes before it leads back to it.
+    pub fn go_to(&mut self, node: NodeId) {
+        let mut child = node;
+        while let Some(parent) = self.parent(child) {
+            let parent_node = &mut self.nodes[parent.0];
+            parent_node.selected = parent_node
+                .children
+                .iter()
+                .position(|&c| c == child)
+                .expect("A node should be one of its parent's children");
+            child = parent;
+        }
+        self.current = node;
+    }
+
+    /// Reads every variation of a game tree, checking each move against the rules. The game
+    /// starts at the root.
+    pub fn from_tree(tree: &SgfTree) -> Result<GoGame, SgfError> {
+        let (root, rest) = tree.nodes.split_first().ok_or(SgfError::UnexpectedEnd)?;
+        let header = GameRecord::read_header(root)?;
+        let node = GameRecord::read_node(root, header.size)?;
+        let mut position = GoBoard::new(header.size);
+        GameRecord::apply_node(&mut position, &node, 1)?;
+
+        let mut game = GoGame {
+            komi: header.komi,
+            handicap: header.handicap,
+            info: header.info,
+            nodes: vec![TreeNode {
+                parent: None,
+                children: Vec::new(),
+                selected: 0,
+                node,
+                position,
+            }],
+            current: Self::ROOT,
+        };
+        game.read_tree(Self::ROOT, rest, &tree.variations, header.size)?;
+        Ok(game)
+    }
+
+    /// The whole game as an SGF tree, with a variation for each branch.
+    pub fn to_tree(&self) -> SgfTree {
+        let header = GameRecord {
+            size: self.board().size(),
+            komi: self.komi,
+            handicap: self.handicap,
+            info: self.info.clone(),
+            nodes: Vec::new(),
+        };
+        let mut root = header.write_header();
+        for (id, values) in GameRecord::write_node(&self.tree_node(Self::ROOT).node).properties() {
+            root.set(id, values.to_vec());
+        }
+        self.write_tree(Self::ROOT, root)
+    }
+
+    pub fn to_sgf(&self) -> String {
+        format!("{}\n", self.to_tree())
+    }
+
+    fn tree_node(&self, node: NodeId) -> &TreeNode {
+        &self.nodes[node.0]
+    }
+
+    fn add_variation_as(
+        &mut self,
+        parent: NodeId,
+        player: GoPlayer,
+        play: Move,
+    ) -> Result<NodeId, GoBoardError> {
+        let existing = self
+            .children(parent)
+            .iter()
+            .find(|&&child| self.node_move(child) == Some((player, play)));
+        if let Some(&node) = existing {
+            return Ok(node);
+        }
+
+        let mut position = self.position(parent).clone();
+        position.whos_turn = player;
+        match play {
+            Move::Play(row, column) => position.make_move(row, column)?,
+            Move::Pass => position.pass(),
+        }
+        let node = GameNode {
+            play: Some((player, play)),
+            ..GameNode::default()
+        };
+        Ok(self.push(parent, node, position))
+    }
+
+    fn push(&mut self, parent: NodeId, node: GameNode, position: GoBoard) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(TreeNode {
+            parent: Some(parent),
+            children: Vec::new(),
+            selected: 0,
+            node,
+            position,
+        });
+        self.nodes[parent.0].children.push(id);
+        id
+    }
+
+    /// Reads the nodes and variations following `parent`. The variations still to read are
+    /// kept on a stack rather than recursing, so deeply nested variations cannot overflow the
+    /// call stack.
+    fn read_tree(
+        &mut self,
+        parent: NodeId,
+        nodes: &[SgfNode],
+        variations: &[SgfTree],
+        size: usize,
+    ) -> Result<(), SgfError> {
+        let moves = self
+            .line(parent)
+            .into_iter()
+            .filter(|&n| self.node_move(n).is_some());
+        let mut pending = vec![(parent, moves.count(), nodes, variations)];
+        while let Some((mut parent, mut moves, nodes, variations)) = pending.pop() {
+            for node in nodes {
+                let node = GameRecord::read_node(node, size)?;
+                if node.play.is_some() {
+                    moves += 1;
+                }
+                let mut position = self.position(parent).clone();
+                GameRecord::apply_node(&mut position, &node, moves)?;
+                parent = self.push(parent, node, position);
+            }
+            // Reversed, so that the first variation is read first and stays the main line
+            pending.extend(variations.iter().rev().map(|variation| {
+                (
+                    parent,
+                    moves,
+                    &variation.nodes[..],
+                    &variation.variations[..],
+                )
+            }));
+        }
+        Ok(())
+    }
+
+    /// Writes the nodes from `first` up to the next branch, followed by a variation for each
+    /// branch. The trees still open are kept on a stack rather than recursing.
+    fn write_tree(&self, first: NodeId, written: SgfNode) -> SgfTree {
+        let mut open: Vec<(SgfTree, std::slice::Iter<NodeId>)> = Vec::new();
+        let mut next = Some((first, written));
+        loop {
+            if let Some((mut node, written)) = next.take() {
+                let mut tree = SgfTree {
+                    nodes: vec![written],
+                    variations: Vec::new(),
+                };
+                while let [only] = self.children(node) {
+                    tree.nodes
+                        .push(GameRecord::write_node(&self.tree_node(*only).node));
+                    node = *only;
+                }
+                open.push((tree, self.children(node).iter()));
+            }
+
+            let (_, children) = open.last_mut().expect("A tree should be open");
+            match children.next() {
+                Some(&child) => {
+                    next = Some((child, GameRecord::write_node(&self.tree_node(child).node)));
+                }
+                None => {
+                    let (done, _) = open.pop().expect("A tree should be open");
+                    match open.last_mut() {
+                        Some((parent, _)) => parent.variations.push(done),
+                        None => return done,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Reads the first game in a file, as for [`GoGame::from_tree`].
+impl FromStr for GoGame {
+    type Err = SgfError;
+
+    fn from_str(as_str: &str) -> Result<GoGame, SgfError> {
+        GoGame::from_tree(&SgfTree::from_str(as_str)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn fixture(name: &str) -> String {
+        let filename = format!("resources/tests/sgf/{name}.sgf");
+        fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Expected to find hardcoded test resource at {filename}"))
+    }
+
+    #[test]
+    fn test_undo_and_redo() {
+        let mut game = GoGame::new(5);
+        game.play(Move::Play(2, 2)).unwrap();
+        let second = game.play(Move::Play(1, 1)).unwrap();
+        let after_second = game.board().clone();
+
+        assert!(game.undo());
+        assert_eq!(game.board(), &game.position(NodeId(1)).clone());
+        assert!(game.undo());
+        assert_eq!(game.board(), &GoBoard::new(5));
+        assert!(!game.undo());
+
+        assert!(game.redo());
+        assert!(game.redo());
+        assert!(!game.redo());
+        assert_eq!(game.current(), second);
+        assert_eq!(game.board(), &after_second);
+        assert_eq!(
+            game.moves(),
+            [
+                (GoPlayer::Black, Move::Play(2, 2)),
+                (GoPlayer::White, Move::Play(1, 1))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_playing_a_known_move_reuses_its_node() {
+        let mut game = GoGame::new(5);
+        let first = game.play(Move::Play(2, 2)).unwrap();
+        game.undo();
+
+        let result = game.play(Move::Play(2, 2)).unwrap();
+
+        assert_eq!(result, first);
+        assert_eq!(game.children(GoGame::ROOT), [first]);
+    }
+
+    #[test]
+    fn test_variations() {
+        let mut game = GoGame::new(5);
+        let main = game.play(Move::Play(2, 2)).unwrap();
+        game.undo();
+        let variation = game.play(Move::Play(0, 0)).unwrap();
+
+        assert_eq!(game.children(GoGame::ROOT), [main, variation]);
+        assert_eq!(
+            game.position(variation).stone(0, 0),
+            Ok(Some(GoPlayer::Black))
+        );
+        assert_eq!(game.position(variation).stone(2, 2), Ok(None));
+
+        // Redo follows the variation visited last
+        game.undo();
+        game.redo();
+        assert_eq!(game.current(), variation);
+
+        assert!(game.switch_variation(0));
+        assert_eq!(game.current(), main);
+        assert!(!game.switch_variation(2));
+        game.undo();
+        game.redo();
+        assert_eq!(game.current(), main);
+    }
+
+    #[test]
+    fn test_add_variation_keeps_the_current_node() {
+        let mut game = GoGame::new(5);
+        let first = game.play(Move::Play(2, 2)).unwrap();
+        let second = game.play(Move::Play(1, 1)).unwrap();
+
+        let result = game.add_variation(first, Move::Pass).unwrap();
+
+        assert_eq!(game.current(), second);
+        assert_eq!(game.children(first), [second, result]);
+        assert_eq!(game.node_move(result), Some((GoPlayer::White, Move::Pass)));
+        assert_eq!(game.position(result).whos_turn(), GoPlayer::Black);
+    }
+
+    #[test]
+    fn test_go_to_any_node() {
+        let mut game = GoGame::new(5);
+        game.play(Move::Play(2, 2)).unwrap();
+        let deep = game.play(Move::Play(1, 1)).unwrap();
+        let side = game.add_variation(GoGame::ROOT, Move::Play(4, 4)).unwrap();
+
+        game.go_to(side);
+        assert_eq!(game.line(side), [GoGame::ROOT, side]);
+        assert_eq!(game.moves(), [(GoPlayer::Black, Move::Play(4, 4))]);
+
+        game.go_to(deep);
+        game.go_to(GoGame::ROOT);
+        game.redo();
+        game.redo();
+        assert_eq!(game.current(), deep);
+    }
+
+    #[test]
+    fn test_illegal_move_changes_nothing() {
+        let mut game = GoGame::new(5);
+        game.play(Move::Play(2, 2)).unwrap();
+        let before = game.clone();
+
+        let result = game.play(Move::Play(2, 2));
+
+        assert_eq!(result, Err(GoBoardError::Occupied));
+        assert_eq!(game, before);
+    }
+
+    #[test]
+    fn test_to_sgf() {
+        let mut game = GoGame::new(5);
+        game.komi = 0.5;
+        game.play(Move::Play(2, 2)).unwrap();
+        let reply = game.play(Move::Play(1, 1)).unwrap();
+        game.play(Move::Play(3, 3)).unwrap();
+        game.set_comment(reply, Some("The main line".to_string()));
+        game.add_variation(reply, Move::Pass).unwrap();
+        game.add_variation(NodeId(1), Move::Play(3, 3)).unwrap();
+
+        let result = game.to_sgf();
+
+        assert_eq!(
+            result,
+            "(;FF[4]GM[1]CA[UTF-8]SZ[5]KM[0.5]\n;B[cc]\n(;W[bb]C[The main line]\n(;B[dd])\n(;B[]))\n(;W[dd]))\n"
+        );
+        let mut read = GoGame::from_str(&result).unwrap();
+        read.go_to(game.current());
+        assert_eq!(read, game);
+    }
+
+    #[test]
+    fn test_from_sgf_fixture() {
+        let mut game = GoGame::from_str(&fixture("variations")).unwrap();
+
+        assert_eq!(game.current(), GoGame::ROOT);
+        while game.redo() {}
+        let main_line = GameRecord::from_str(&fixture("variations"))
+            .unwrap()
+            .replay()
+            .unwrap();
+        assert_eq!(game.board(), &main_line);
+
+        game.undo();
+        assert!(game.switch_variation(1));
+        assert_eq!(game.comment(game.current()), Some("Not the main line"));
+        assert_eq!(game.moves().len(), 2);
+    }
+
+    #[test]
+    fn test_deeply_nested_variations() {
+        let depth = 100_000;
+        let input = format!(
+            "(;SZ[2]{}{})",
+            "(;C[a]".repeat(depth),
+            "(;C[b]))".repeat(depth)
+        );
+
+        let game = GoGame::from_str(&input).unwrap();
+        let result = GoGame::from_str(&game.to_sgf()).unwrap();
+
+        let main_line = |game: &GoGame| {
+            std::iter::successors(Some(GoGame::ROOT), |&n| game.children(n).first().copied())
+                .count()
+        };
+        assert_eq!(main_line(&game), depth + 2);
+        assert_eq!(main_line(&result), depth + 2);
+        assert_eq!(result.to_sgf(), game.to_sgf());
+    }
+
+    #[test]
+    fn test_from_sgf_checks_every_variation() {
+        let input = "(;SZ[5];B[cc](;W[dd])(;W[cc]))";
+
+        let result = GoGame::from_str(input);
+
+        assert_eq!(
+            result,
+            Err(SgfError::IllegalMove {
+                move_number: 2,
+                error: GoBoardError::Occupied
+            })
+        );
+    }
+}
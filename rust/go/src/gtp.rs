@@ -0,0 +1,360 @@
+//! An engine speaking version 2 of the [Go Text Protocol](https://www.lysator.liu.se/~gunnar/gtp/),
+//! so that games on a [`GoBoard`] can be played through controllers such as GoGui or Sabaki.
+
+use crate::{GoBoard, GoBoardError, GoCell, GoPlayer, Move};
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
+
+/// Every command the engine answers, as given by `list_commands`.
+const COMMANDS: [&str; 14] = [
+    "protocol_version",
+    "name",
+    "version",
+    "known_command",
+    "list_commands",
+    "quit",
+    "boardsize",
+    "clear_board",
+    "komi",
+    "play",
+    "genmove",
+    "undo",
+    "showboard",
+    "final_score",
+];
+
+const NAME: &str = "go-gtp";
+
+/// Board sizes that vertices can be written for, with one letter per column.
+const SIZES: RangeInclusive<usize> = 2..=25;
+
+const DEFAULT_SIZE: usize = 19;
+
+#[derive(Debug, Clone)]
+pub struct GtpEngine {
+    board: GoBoard,
+    komi: f64,
+    /// The position before each move played, most recent last, for `undo`.
+    history: Vec<GoBoard>,
+}
+
+impl Default for GtpEngine {
+    fn default() -> Self {
+        GtpEngine::new(DEFAULT_SIZE)
+    }
+}
+
+impl GtpEngine {
+    pub fn new(size: usize) -> GtpEngine {
+        GtpEngine {
+            board: GoBoard::new(size),
+            komi: 0.0,
+            history: Vec::new(),
+        }
+    }
+
+    /// Answers each command read from `input` until `quit` or the end of the input.
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+        for line in input.lines() {
+            let line = line?;
+            let Some((id, words)) = Self::preprocess(&line) else {
+                continue;
+            };
+            let (command, args) = words.split_first().unwrap_or((&"", &[]));
+
+            let (status, text) = match self.execute(command, args) {
+                Ok(text) => ('=', text),
+                Err(text) => ('?', text),
+            };
+            let id = id.map(|id| id.to_string()).unwrap_or_default();
+            // Responses spanning several lines start on the line after the status
+            let separator = match text.is_empty() || text.starts_with('\n') {
+                true => "",
+                false => " ",
+            };
+            write!(output, "{status}{id}{separator}{text}\n\n")?;
+            output.flush()?;
+
+            if *command == "quit" {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Answers a single command, giving the text of a successful response or of an error.
+    pub fn execute(&mut self, command: &str, args: &[&str]) -> Result<String, String> {
+        match (command, args) {
+            ("protocol_version", []) => Ok("2".to_string()),
+            ("name", []) => Ok(NAME.to_string()),
+            ("version", []) => Ok(env!("CARGO_PKG_VERSION").to_string()),
+            ("known_command", [name]) => Ok(COMMANDS.contains(name).to_string()),
+            ("list_commands", []) => Ok(COMMANDS.join("\n")),
+            ("quit", []) => Ok(String::new()),
+            ("boardsize", [size]) => {
+                let size: usize = size.parse().map_err(|_| Self::syntax_error())?;
+                if !SIZES.contains(&size) {
+                    return Err("unacceptable size".to_string());
+                }
+                *self = GtpEngine {
+                    komi: self.komi,
+                    ..GtpEngine::new(size)
+                };
+                Ok(String::new())
+            }
+            ("clear_board", []) => {
+                self.board = GoBoard::new(self.size());
+                self.history.clear();
+                Ok(String::new())
+            }
+            ("komi", [komi]) => {
+                self.komi = komi.parse().map_err(|_| Self::syntax_error())?;
+                Ok(String::new())
+            }
+            ("play", [colour, vertex]) => {
+                let player = Self::parse_colour(colour)?;
+                let play = Move::from_vertex(vertex, self.size()).ok_or_else(Self::syntax_error)?;
+                self.play(player, play)
+                    .map(|_| String::new())
+                    .map_err(|_| "illegal move".to_string())
+            }
+            ("genmove", [colour]) => {
+                let player = Self::parse_colour(colour)?;
+                let play = self.choose_move(player);
+                self.play(player, play)
+                    .expect("Chosen moves should be legal");
+                Ok(play.to_vertex(self.size()))
+            }
+            ("undo", []) => match self.history.pop() {
+                Some(board) => {
+                    self.board = board;
+                    Ok(String::new())
+                }
+                None => Err("cannot undo".to_string()),
+            },
+            ("showboard", []) => Ok(format!("\n{}", self.board.to_diagram().trim_end())),
+            ("final_score", []) => Ok(self.board.score(self.komi).to_string()),
+            _ if COMMANDS.contains(&command) => Err(Self::syntax_error()),
+            _ => Err("unknown command".to_string()),
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.board.size()
+    }
+
+    /// Plays a move for the given player, whoever's turn it was.
+    fn play(&mut self, player: GoPlayer, play: Move) -> Result<(), GoBoardError> {
+        let mut next = self.board.clone();
+        next.whos_turn = player;
+        match play {
+            Move::Play(row, column) => next.make_move(row, column)?,
+            Move::Pass => next.pass(),
+        }
+        self.history.push(std::mem::replace(&mut self.board, next));
+        Ok(())
+    }
+
+    /// Picks the legal move capturing the most stones, then leaving the played stone's group with
+    /// the most liberties, then nearest the centre. The player never fills in one of their own
+    /// eyes, and passes when there is nothing else to play.
+    fn choose_move(&self, player: GoPlayer) -> Move {
+        let centre = self.size() / 2;
+        let mut best: Option<((u16, usize, isize), Move)> = None;
+        for cell in self.board.board.all_cells() {
+            let (row, column) = (cell.row(), cell.column());
+            if cell.value() != GoCell::Empty || self.is_eye(row, column, player) {
+                continue;
+            }
+
+            let mut next = self.board.clone();
+            next.whos_turn = player;
+            if next.make_move(row, column).is_err() {
+                continue;
+            }
+            let captured = next.captures[&player] - self.board.captures[&player];
+            let liberties = next
+                .board
+                .regions(GoBoard::NEIGHBOURHOOD)
+                .region_at(row, column)
+                .map_or(0, |region| GoBoard::liberties(region).count());
+            let distance = row.abs_diff(centre) + column.abs_diff(centre);
+
+            let key = (captured, liberties, -(distance as isize));
+            if best.is_none_or(|(best_key, _)| key > best_key) {
+                best = Some((key, Move::Play(row, column)));
+            }
+        }
+        best.map_or(Move::Pass, |(_, play)| play)
+    }
+
+    /// Whether every point next to an empty point holds one of the player's stones.
+    fn is_eye(&self, row: usize, column: usize, player: GoPlayer) -> bool {
+        self.board
+            .board
+            .get_neighbours(row, column, GoBoard::NEIGHBOURHOOD)
+            .all(|neighbour| GoPlayer::try_from(neighbour.value()) == Ok(player))
+    }
+
+    /// Drops comments and control characters, then splits off the command's id if it has one.
+    /// Lines with nothing left are ignored.
+    fn preprocess(line: &str) -> Option<(Option<u32>, Vec<&str>)> {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut words: Vec<&str> = line
+            .split(|c: char| c.is_whitespace() || c.is_control())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let id = words.first().and_then(|word| word.parse().ok());
+        if id.is_some() {
+            words.remove(0);
+        }
+        (!words.is_empty() || id.is_some()).then_some((id, words))
+    }
+
+    fn parse_colour(colour: &str) -> Result<GoPlayer, String> {
+        match colour.to_ascii_lowercase().as_str() {
+            "b" | "black" => Ok(GoPlayer::Black),
+            "w" | "white" => Ok(GoPlayer::White),
+            _ => Err(Self::syntax_error()),
+        }
+    }
+
+    fn syntax_error() -> String {
+        "syntax error".to_string()
+    }
+}
+
+impl Move {
+    /// Reads a vertex as written in GTP, a column letter then a row counted up from the bottom of
+    /// the board such as `D4`, or `pass`.
+    pub fn from_vertex(vertex: &str, size: usize) -> Option<Move> {
+        if vertex.eq_ignore_ascii_case("pass") {
+            return Some(Move::Pass);
+        }
+        let mut chars = vertex.chars();
+        let letter = chars.next()?.to_ascii_uppercase();
+        let column = GoBoard::COLUMN_LABELS.find(letter)?;
+        let number: usize = chars.as_str().parse().ok()?;
+        (column < size && (1..=size).contains(&number)).then(|| Move::Play(size - number, column))
+    }
+
+    pub fn to_vertex(self, size: usize) -> String {
+        match self {
+            Move::Play(row, column) => {
+                let letter = GoBoard::COLUMN_LABELS.as_bytes()[column] as char;
+                format!("{letter}{}", size - row)
+            }
+            Move::Pass => "pass".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+    use test_case::test_case;
+
+    fn engine(as_str: &str) -> GtpEngine {
+        let board = GoBoard::from_str(as_str).expect("GoBoard should have parsed test input");
+        GtpEngine {
+            board,
+            komi: 0.0,
+            history: Vec::new(),
+        }
+    }
+
+    #[test_case("A1", 9, Some(Move::Play(8, 0)))]
+    #[test_case("j9", 9, Some(Move::Play(0, 8)))]
+    #[test_case("T19", 19, Some(Move::Play(0, 18)))]
+    #[test_case("PASS", 9, Some(Move::Pass))]
+    #[test_case("I5", 9, None)]
+    #[test_case("A10", 9, None)]
+    #[test_case("K1", 9, None)]
+    #[test_case("A0", 9, None)]
+    fn test_from_vertex(vertex: &str, size: usize, expected: Option<Move>) {
+        let result = Move::from_vertex(vertex, size);
+
+        assert_eq!(result, expected);
+        if let Some(play) = result {
+            assert!(play.to_vertex(size).eq_ignore_ascii_case(vertex));
+        }
+    }
+
+    #[test_case("  genmove   b ", Some((None, vec!["genmove", "b"])))]
+    #[test_case("12 play\tw D4 # a comment", Some((Some(12), vec!["play", "w", "D4"])))]
+    #[test_case("# only a comment", None)]
+    #[test_case("7", Some((Some(7), vec![])))]
+    fn test_preprocess(line: &str, expected: Option<(Option<u32>, Vec<&str>)>) {
+        assert_eq!(GtpEngine::preprocess(line), expected);
+    }
+
+    #[test]
+    fn test_genmove_prefers_captures() {
+        let mut state = engine(
+            "turn=B\nlast_move=ok\ncapturesW=0\ncapturesB=0\nWB---\n-----\n-----\n-----\n-----",
+        );
+
+        let result = state.execute("genmove", &["b"]);
+
+        assert_eq!(result, Ok("A4".to_string()));
+        assert_eq!(state.board.captures[&GoPlayer::Black], 1);
+    }
+
+    #[test]
+    fn test_genmove_opens_in_the_centre() {
+        let mut state = GtpEngine::new(9);
+
+        let result = state.execute("genmove", &["b"]);
+
+        assert_eq!(result, Ok("E5".to_string()));
+    }
+
+    #[test]
+    fn test_genmove_passes_rather_than_fill_eyes() {
+        let mut state = engine("turn=B\nlast_move=ok\ncapturesW=0\ncapturesB=0\n-B-\nB-B\n-B-");
+
+        let result = state.execute("genmove", &["black"]);
+
+        assert_eq!(result, Ok("pass".to_string()));
+    }
+
+    #[test]
+    fn test_undo() {
+        let mut state = GtpEngine::new(5);
+        let before = state.board.clone();
+
+        state.execute("play", &["b", "C3"]).unwrap();
+        state.execute("play", &["w", "pass"]).unwrap();
+
+        assert_eq!(state.execute("undo", &[]), Ok(String::new()));
+        assert_eq!(state.execute("undo", &[]), Ok(String::new()));
+        assert_eq!(state.board, before);
+        assert_eq!(state.execute("undo", &[]), Err("cannot undo".to_string()));
+    }
+
+    #[test_case("play", &["b", "Z9"], "syntax error")]
+    #[test_case("play", &["red", "C3"], "syntax error")]
+    #[test_case("play", &["b"], "syntax error")]
+    #[test_case("boardsize", &["26"], "unacceptable size")]
+    #[test_case("komi", &["lots"], "syntax error")]
+    #[test_case("fly", &[], "unknown command")]
+    fn test_errors(command: &str, args: &[&str], expected: &str) {
+        let result = GtpEngine::new(5).execute(command, args);
+
+        assert_eq!(result, Err(expected.to_string()));
+    }
+
+    #[test]
+    fn test_boardsize_keeps_komi() {
+        let mut state = GtpEngine::new(5);
+        state.execute("komi", &["7.5"]).unwrap();
+        state.execute("play", &["b", "C3"]).unwrap();
+
+        state.execute("boardsize", &["9"]).unwrap();
+
+        assert_eq!(state.board, GoBoard::new(9));
+        assert_eq!(state.execute("final_score", &[]), Ok("W+7.5".to_string()));
+        assert_eq!(state.execute("undo", &[]), Err("cannot undo".to_string()));
+    }
+}
@@ -1,15 +1,35 @@
 use arr2d::Arr2d;
+use arr2d::Arr2dError;
 use arr2d::Cell;
+use arr2d::CharCodec;
+use arr2d::Colour;
+use arr2d::ImageError;
+use arr2d::ImageFormat;
+use arr2d::InvalidChar;
+use arr2d::Neighbourhood;
+use arr2d::Palette;
 use arr2d::ParseError;
+use arr2d::Region;
+use arr2d::RegionId;
+use arr2d::RleFormat;
+use arr2d::is_rle;
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::collections::VecDeque;
 use std::fmt;
 use std::hash::Hash;
 use std::str::FromStr;
 
+mod game;
+mod gtp;
+mod sgf;
+
+pub use game::{GoGame, NodeId};
+pub use gtp::GtpEngine;
+pub use sgf::{GameInfo, GameNode, GameRecord, Move, Setup, SgfError, SgfNode, SgfTree};
+
 #[derive(Hash, Eq, Debug, PartialEq, Copy, Clone)]
-enum GoPlayer {
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GoPlayer {
     White,
     Black,
 }
@@ -36,9 +56,9 @@ impl GoPlayer {
     }
 }
 
-impl Into<GoCell> for GoPlayer {
-    fn into(self) -> GoCell {
-        match self {
+impl From<GoPlayer> for GoCell {
+    fn from(value: GoPlayer) -> GoCell {
+        match value {
             GoPlayer::White => GoCell::White,
             GoPlayer::Black => GoCell::Black,
         }
@@ -46,20 +66,20 @@ impl Into<GoCell> for GoPlayer {
 }
 
 impl TryFrom<char> for GoPlayer {
-    type Error = ParseError;
+    type Error = InvalidChar;
 
-    fn try_from(c: char) -> Result<GoPlayer, ParseError> {
+    fn try_from(c: char) -> Result<GoPlayer, InvalidChar> {
         match c {
             GoBoard::WHITE => Ok(GoPlayer::White),
             GoBoard::BLACK => Ok(GoPlayer::Black),
-            _ => Err(ParseError::InvalidCharacter),
+            _ => Err(InvalidChar(c)),
         }
     }
 }
 
-impl Into<char> for GoPlayer {
-    fn into(self) -> char {
-        match self {
+impl From<GoPlayer> for char {
+    fn from(value: GoPlayer) -> char {
+        match value {
             GoPlayer::White => GoBoard::WHITE,
             GoPlayer::Black => GoBoard::BLACK,
         }
@@ -75,7 +95,8 @@ impl GoPlayer {
     }
 }
 
-#[derive(Hash, Eq, Debug, PartialEq)]
+#[derive(Hash, Eq, Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum LastMove {
     Ok,
     IllegalKo,
@@ -92,19 +113,34 @@ impl fmt::Display for LastMove {
 }
 
 impl FromStr for LastMove {
-    type Err = ParseError;
+    type Err = ();
 
-    fn from_str(s: &str) -> Result<Self, ParseError> {
+    fn from_str(s: &str) -> Result<Self, ()> {
         match s {
             "ok" => Result::Ok(LastMove::Ok),
             "illegal_ko" => Result::Ok(LastMove::IllegalKo),
             "illegal_suicidal" => Result::Ok(LastMove::IllegalSuicidal),
-            _ => Err(ParseError::InvalidValue),
+            _ => Err(()),
         }
     }
 }
 
+/// Which earlier positions a move may not bring back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KoRule {
+    /// A move may not bring back the position from before the opponent's last move, which
+    /// forbids retaking a ko straight away but allows longer cycles such as triple ko.
+    #[default]
+    Simple,
+    /// A move may not bring back any earlier position.
+    PositionalSuperko,
+    /// A move may not bring back any earlier position with the same player to move.
+    SituationalSuperko,
+}
+
 #[derive(Debug, Clone, PartialEq, Copy, Hash, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum GoCell {
     White,
     WhitePending,
@@ -113,19 +149,40 @@ enum GoCell {
     Empty,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum GoBoardError {
-    IllegalMove,
+    IllegalKo,
+    IllegalSuicide,
     InvalidPlayer,
     NoPendingFound,
     WrongPlayerTurn,
+    OffBoard(Arr2dError),
+    Occupied,
 }
 
-impl TryInto<GoPlayer> for GoCell {
+impl fmt::Display for GoBoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GoBoardError::IllegalKo => write!(f, "the move retakes a ko"),
+            GoBoardError::IllegalSuicide => {
+                write!(f, "the move would leave its own group without liberties")
+            }
+            GoBoardError::InvalidPlayer => write!(f, "the point does not hold a player's stone"),
+            GoBoardError::NoPendingFound => write!(f, "there is no pending stone to play"),
+            GoBoardError::WrongPlayerTurn => write!(f, "it is the other player's turn"),
+            GoBoardError::OffBoard(e) => write!(f, "{e}"),
+            GoBoardError::Occupied => write!(f, "the point already holds a stone"),
+        }
+    }
+}
+
+impl std::error::Error for GoBoardError {}
+
+impl TryFrom<GoCell> for GoPlayer {
     type Error = GoBoardError;
 
-    fn try_into(self) -> Result<GoPlayer, GoBoardError> {
-        match self {
+    fn try_from(value: GoCell) -> Result<GoPlayer, GoBoardError> {
+        match value {
             GoCell::White => Ok(GoPlayer::White),
             GoCell::WhitePending => Ok(GoPlayer::White),
             GoCell::Black => Ok(GoPlayer::Black),
@@ -152,23 +209,23 @@ impl fmt::Display for GoCell {
 }
 
 impl TryFrom<char> for GoCell {
-    type Error = ParseError;
+    type Error = InvalidChar;
 
-    fn try_from(c: char) -> Result<GoCell, ParseError> {
+    fn try_from(c: char) -> Result<GoCell, InvalidChar> {
         match c {
             GoBoard::WHITE => Ok(GoCell::White),
             GoBoard::WHITE_PENDING => Ok(GoCell::WhitePending),
             GoBoard::BLACK => Ok(GoCell::Black),
             GoBoard::BLACK_PENDING => Ok(GoCell::BlackPending),
             GoBoard::EMPTY => Ok(GoCell::Empty),
-            _ => Err(ParseError::InvalidCharacter),
+            _ => Err(InvalidChar(c)),
         }
     }
 }
 
-impl Into<char> for GoCell {
-    fn into(self) -> char {
-        match self {
+impl From<GoCell> for char {
+    fn from(value: GoCell) -> char {
+        match value {
             GoCell::White => GoBoard::WHITE,
             GoCell::WhitePending => GoBoard::WHITE_PENDING,
             GoCell::Black => GoBoard::BLACK,
@@ -178,26 +235,86 @@ impl Into<char> for GoCell {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// With the `serde` feature, a board is saved without its hash or earlier positions. Both are
+/// rebuilt from the stones when it is read back, so a read board has no earlier positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "SavedBoard")
+)]
 pub struct GoBoard {
     whos_turn: GoPlayer,
     last_move: LastMove,
     captures: HashMap<GoPlayer, u16>,
-    last_captures: VecDeque<String>,
+    ko_rule: KoRule,
+    /// The Zobrist hash of the stones on the board, updated as stones are placed and captured.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hash: u64,
+    /// The hash of every position reached and who was to move in it, oldest first and ending
+    /// with the current position.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: Vec<(u64, GoPlayer)>,
+    board: Arr2d<GoCell>,
+}
+
+/// The fields of a [`GoBoard`] that are saved, read back through [`GoBoard::from_parts`] so the
+/// hash always matches the stones. Boards saved before ko rules were added read as
+/// [`KoRule::Simple`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SavedBoard {
+    whos_turn: GoPlayer,
+    last_move: LastMove,
+    captures: HashMap<GoPlayer, u16>,
+    #[serde(default)]
+    ko_rule: KoRule,
     board: Arr2d<GoCell>,
 }
 
+#[cfg(feature = "serde")]
+impl From<SavedBoard> for GoBoard {
+    fn from(saved: SavedBoard) -> GoBoard {
+        let mut state = GoBoard::from_parts(saved.board, saved.whos_turn, saved.captures);
+        state.last_move = saved.last_move;
+        state.ko_rule = saved.ko_rule;
+        state
+    }
+}
+
+/// The result of counting a finished game by area: each player's stones on the board plus the
+/// empty points surrounded only by their stones, with komi added for white.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Score {
+    pub black: f64,
+    pub white: f64,
+}
+
+impl Score {
+    /// The player with more points, or `None` for a draw.
+    pub fn winner(&self) -> Option<GoPlayer> {
+        match self.black - self.white {
+            margin if margin > 0.0 => Some(GoPlayer::Black),
+            margin if margin < 0.0 => Some(GoPlayer::White),
+            _ => None,
+        }
+    }
+}
+
+/// Written as the winner and their margin, such as `B+3.5`, or `0` for a draw, as in SGF and
+/// GTP.
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.winner() {
+            Some(winner) => write!(f, "{winner}+{}", (self.black - self.white).abs()),
+            None => write!(f, "0"),
+        }
+    }
+}
+
 impl fmt::Display for GoBoard {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "turn={}\nlast_move={}\ncapturesW={}\ncapturesB={}\n{}",
-            self.whos_turn,
-            self.last_move,
-            self.captures.get(&GoPlayer::White).unwrap(),
-            self.captures.get(&GoPlayer::Black).unwrap(),
-            self.board
-        )
+        write!(f, "{}{}", self.header(), self.board)
     }
 }
 
@@ -208,86 +325,311 @@ impl GoBoard {
     pub const BLACK_PENDING: char = 'b';
     pub const EMPTY: char = '-';
 
-    fn locate_pending(&self) -> Option<&Cell<GoCell>> {
-        self.board
+    const NEIGHBOURHOOD: &Neighbourhood = &Neighbourhood::VonNeumann;
+
+    pub fn size(&self) -> usize {
+        self.board.width()
+    }
+
+    pub fn whos_turn(&self) -> GoPlayer {
+        self.whos_turn
+    }
+
+    /// The number of stones the player has captured.
+    pub fn captures(&self, player: GoPlayer) -> u16 {
+        self.captures[&player]
+    }
+
+    /// Whose stone is at a point, if any.
+    pub fn stone(&self, row: usize, column: usize) -> Result<Option<GoPlayer>, GoBoardError> {
+        let cell = self
+            .board
+            .try_get(row, column)
+            .map_err(GoBoardError::OffBoard)?;
+        Ok(GoPlayer::try_from(*cell).ok())
+    }
+
+    /// Labels for the columns of a board, skipping `I` so it cannot be mistaken for `J`, as is the
+    /// convention for Go diagrams.
+    pub const COLUMN_LABELS: &str = "ABCDEFGHJKLMNOPQRSTUVWXYZ";
+
+    /// An empty square board with black to play.
+    pub fn new(size: usize) -> GoBoard {
+        Self::from_parts(
+            Arr2d::from_fn(size, size, |_, _| GoCell::Empty),
+            GoPlayer::Black,
+            HashMap::from([(GoPlayer::White, 0), (GoPlayer::Black, 0)]),
+        )
+    }
+
+    /// A board with no earlier positions, hashing the stones already on it.
+    fn from_parts(
+        board: Arr2d<GoCell>,
+        whos_turn: GoPlayer,
+        captures: HashMap<GoPlayer, u16>,
+    ) -> GoBoard {
+        let mut state = GoBoard {
+            whos_turn,
+            last_move: LastMove::Ok,
+            captures,
+            ko_rule: KoRule::default(),
+            hash: 0,
+            history: Vec::new(),
+            board,
+        };
+        state.hash = state
+            .board
             .all_cells()
-            .filter(|c| match c.value() {
-                GoCell::WhitePending | GoCell::BlackPending => true,
-                _ => false,
+            .filter_map(|c| match c.value() {
+                GoCell::Black => Some(state.zobrist_key(c.row(), c.column(), GoPlayer::Black)),
+                GoCell::White => Some(state.zobrist_key(c.row(), c.column(), GoPlayer::White)),
+                _ => None,
             })
-            .next()
+            .fold(0, |hash, key| hash ^ key);
+        state.history.push((state.hash, whos_turn));
+        state
     }
 
-    fn calculate_captures(
-        &self,
-        from: &Cell<GoCell>,
-        opponent: GoPlayer,
-    ) -> impl Iterator<Item = &Cell<GoCell>> {
-        let opponent_cell: GoCell = opponent.into();
-        let mut seen_ids = HashSet::new();
+    /// The same position, played on under the given ko rule.
+    pub fn with_ko_rule(mut self, ko_rule: KoRule) -> GoBoard {
+        self.ko_rule = ko_rule;
+        self
+    }
+
+    pub fn ko_rule(&self) -> KoRule {
+        self.ko_rule
+    }
+
+    /// The Zobrist hash of the stones on the board, which is the same for the same stones
+    /// however they came to be there.
+    pub fn zobrist_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// The key of a stone of the given colour at a point, mixed from the point's index with
+    /// SplitMix64 so that every board gets the same keys without storing a table.
+    fn zobrist_key(&self, row: usize, column: usize, player: GoPlayer) -> u64 {
+        let colour = match player {
+            GoPlayer::Black => 0,
+            GoPlayer::White => 1,
+        };
+        let index = ((row * self.board.width() + column) * 2 + colour) as u64;
+        let mut key = (index + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        key = (key ^ (key >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        key = (key ^ (key >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        key ^ (key >> 31)
+    }
+
+    /// Puts a stone on a point, or empties it, without playing a move, as for setting up a
+    /// position. The result replaces the current position in the history.
+    pub(crate) fn set_stone(&mut self, row: usize, column: usize, stone: Option<GoPlayer>) {
+        // Pending stones are not part of the hash until they are played
+        if let Ok(&old @ (GoCell::Black | GoCell::White)) = self.board.try_get(row, column) {
+            self.hash ^= self.zobrist_key(row, column, GoPlayer::try_from(old).unwrap());
+        }
+        if let Some(new) = stone {
+            self.hash ^= self.zobrist_key(row, column, new);
+        }
         self.board
-            .get_neighbours(from.row(), from.column())
-            .filter(move |neighbour| neighbour.value() == opponent_cell)
-            .filter(|neighbour| {
-                !self
-                    .has_liberties(neighbour.row(), neighbour.column())
-                    .unwrap()
-            })
-            .flat_map(|captured_neighbour| {
-                self.board
-                    .flood_fill(captured_neighbour.row(), captured_neighbour.column())
-                    .unwrap()
-            })
-            .filter(move |c| seen_ids.insert(c.id()))
+            .set(row, column, stone.map_or(GoCell::Empty, GoCell::from));
+        *self
+            .history
+            .last_mut()
+            .expect("The history should hold the current position") = (self.hash, self.whos_turn);
+    }
+
+    /// The colour of the wooden board behind the stones in images.
+    const BOARD_COLOUR: Colour = Colour::new(220, 179, 92);
+
+    /// Stones are drawn in their own colours, pending or not, on a wooden board.
+    fn image_format(scale: usize) -> ImageFormat<Palette<GoCell>> {
+        ImageFormat::new(Palette::new([
+            (GoCell::Black, Colour::BLACK),
+            (GoCell::BlackPending, Colour::BLACK),
+            (GoCell::White, Colour::WHITE),
+            (GoCell::WhitePending, Colour::WHITE),
+            (GoCell::Empty, Self::BOARD_COLOUR),
+        ]))
+        .with_scale(scale)
+    }
+
+    /// The position as a raw PGM image, with each point drawn as a square `scale` pixels across.
+    pub fn to_pgm(&self, scale: usize) -> Result<Vec<u8>, ImageError> {
+        Self::image_format(scale).to_pgm(&self.board)
+    }
+
+    /// The position as a colour PNG image, drawn as for [`GoBoard::to_pgm`].
+    #[cfg(feature = "png")]
+    pub fn to_png(&self, scale: usize) -> Result<Vec<u8>, ImageError> {
+        Self::image_format(scale).to_png(&self.board)
+    }
+
+    /// The position with the board run-length encoded, which keeps large, sparse boards short.
+    /// Either layout is read back by [`GoBoard::from_str`].
+    pub fn to_rle(&self) -> String {
+        format!(
+            "{}{}",
+            self.header(),
+            Self::rle_format().format(&self.board)
+        )
+    }
+
+    /// Empty points are the background, so only the stones need writing out.
+    fn rle_format() -> RleFormat<CharCodec, GoCell> {
+        RleFormat::new(CharCodec).with_background(GoCell::Empty)
+    }
+
+    /// The key/value lines written before the board.
+    fn header(&self) -> String {
+        format!(
+            "turn={}\nlast_move={}\ncapturesW={}\ncapturesB={}\n",
+            self.whos_turn,
+            self.last_move,
+            self.captures.get(&GoPlayer::White).unwrap(),
+            self.captures.get(&GoPlayer::Black).unwrap(),
+        )
+    }
+
+    /// The board with columns labelled by letter and rows numbered upwards from the bottom, as
+    /// in Go diagrams, followed by the number of stones each player has captured.
+    pub fn to_diagram(&self) -> String {
+        let labels: String = Self::COLUMN_LABELS
+            .chars()
+            .take(self.board.width())
+            .flat_map(|label| [' ', label])
+            .collect();
+        let mut diagram = format!("  {labels}\n");
+        for (row, cells) in self.board.rows_iter().enumerate() {
+            let number = self.board.height() - row;
+            let cells: String = cells.iter().flat_map(|&c| [' ', c.into()]).collect();
+            diagram.push_str(&format!("{number:>2}{cells} {number}\n"));
+        }
+        diagram.push_str(&format!(
+            "  {labels}\nCaptured by black: {}\nCaptured by white: {}\n",
+            self.captures(GoPlayer::Black),
+            self.captures(GoPlayer::White)
+        ));
+        diagram
+    }
+
+    /// Counts the position by area, adding `komi` to white's points.
+    pub fn score(&self, komi: f64) -> Score {
+        let mut score = Score {
+            black: 0.0,
+            white: komi,
+        };
+        for region in self.board.regions(Self::NEIGHBOURHOOD).iter() {
+            let owner = match region.value() {
+                GoCell::Empty => {
+                    let mut borders = region
+                        .perimeter()
+                        .iter()
+                        .map(|c| GoPlayer::try_from(c.value()));
+                    match borders.next() {
+                        Some(Ok(first)) if borders.all(|b| b == Ok(first)) => Some(first),
+                        _ => None,
+                    }
+                }
+                cell => GoPlayer::try_from(cell).ok(),
+            };
+            match owner {
+                Some(GoPlayer::Black) => score.black += region.size() as f64,
+                Some(GoPlayer::White) => score.white += region.size() as f64,
+                None => {}
+            }
+        }
+        score
+    }
+
+    fn locate_pending(&self) -> Option<Cell<GoCell>> {
+        self.board
+            .all_cells()
+            .find(|c| matches!(c.value(), GoCell::WhitePending | GoCell::BlackPending))
+    }
+
+    /// The opponent groups next to `from` that have no liberties left, as the positions of the
+    /// stones to remove in row-major order.
+    fn calculate_captures(&self, from: &Cell<GoCell>, opponent: GoPlayer) -> Vec<(usize, usize)> {
+        let opponent_cell: GoCell = opponent.into();
+        let regions = self.board.regions(Self::NEIGHBOURHOOD);
+        let captured: HashSet<RegionId> = self
+            .board
+            .get_neighbours(from.row(), from.column(), Self::NEIGHBOURHOOD)
+            .filter(|neighbour| neighbour.value() == opponent_cell)
+            .map(|neighbour| regions.labels()[(neighbour.row(), neighbour.column())])
+            .filter(|&id| Self::liberties(regions.region(id)).next().is_none())
+            .collect();
+
+        regions
+            .labels()
+            .all_cells()
+            .filter(|c| captured.contains(&c.value()))
+            .map(|c| (c.row(), c.column()))
+            .collect()
     }
 
     pub fn make_move(&mut self, row: usize, column: usize) -> Result<(), GoBoardError> {
-        self.board.set(row, column, self.whos_turn.into_pending());
+        let point = self
+            .board
+            .get_mut(row, column)
+            .map_err(GoBoardError::OffBoard)?;
+        if *point != GoCell::Empty {
+            return Err(GoBoardError::Occupied);
+        }
+        *point = self.whos_turn.into_pending();
         self.iterate()
     }
 
+    /// Hands the turn to the other player without placing a stone.
+    pub fn pass(&mut self) {
+        self.whos_turn = self.whos_turn.other();
+        self.history.push((self.hash, self.whos_turn));
+    }
+
+    /// A move that captures nothing must leave the group it joins with a liberty.
     fn check_suicidal(
-        &self,
+        &mut self,
         row: usize,
         column: usize,
-        captures: &Vec<(usize, usize)>,
+        who: GoPlayer,
+        captures: &[(usize, usize)],
     ) -> Result<(), GoBoardError> {
-        // Zero captures, and zero empty neighbours, indicates suicidal move
-        match captures.len() {
-            0 => match self
-                .board
-                .get_neighbours(row, column)
-                .filter(|c| c.value() == GoCell::Empty)
-                .count()
-            {
-                0 => Err(GoBoardError::IllegalMove),
-                _ => Ok(()),
-            },
-            _ => Ok(()),
-        }
-    }
-
-    fn check_ko(&mut self, captures: &Vec<(usize, usize)>) -> Result<(), GoBoardError> {
-        if captures.len() == 0 {
+        if !captures.is_empty() {
             return Ok(());
         }
 
-        let this_str = captures
-            .iter()
-            .map(|(a, b)| format!("{},{}", a, b))
-            .collect::<Vec<String>>()
-            .join("-");
-
-        if self.last_captures.contains(&this_str) {
-            return Err(GoBoardError::IllegalMove);
+        // The pending stone only joins its group once it is played
+        self.board.set(row, column, who.into());
+        let has_liberties = self
+            .board
+            .regions(Self::NEIGHBOURHOOD)
+            .region_at(row, column)
+            .is_some_and(|group| Self::liberties(group).next().is_some());
+        self.board.set(row, column, who.into_pending());
+
+        match has_liberties {
+            true => Ok(()),
+            false => Err(GoBoardError::IllegalSuicide),
         }
-        self.last_captures.push_back(this_str);
+    }
 
-        while self.last_captures.len() > 2 {
-            self.last_captures.pop_front();
+    /// Whether the position with the given hash, with `to_play` to move, is one that the ko rule
+    /// forbids bringing back.
+    fn check_ko(&self, hash: u64, to_play: GoPlayer) -> Result<(), GoBoardError> {
+        let repeated = match self.ko_rule {
+            KoRule::Simple => self
+                .history
+                .iter()
+                .rev()
+                .nth(1)
+                .is_some_and(|&(earlier, _)| earlier == hash),
+            KoRule::PositionalSuperko => self.history.iter().any(|&(earlier, _)| earlier == hash),
+            KoRule::SituationalSuperko => self.history.contains(&(hash, to_play)),
+        };
+        match repeated {
+            true => Err(GoBoardError::IllegalKo),
+            false => Ok(()),
         }
-
-        Ok(())
     }
 
     pub fn iterate(&mut self) -> Result<(), GoBoardError> {
@@ -295,10 +637,7 @@ impl GoBoard {
             Some(c) => c,
             None => return Err(GoBoardError::NoPendingFound),
         };
-        let who: GoPlayer = match cell.value().try_into() {
-            Ok(w) => w,
-            Err(e) => return Err(e),
-        };
+        let who: GoPlayer = cell.value().try_into()?;
 
         if who != self.whos_turn {
             return Err(GoBoardError::WrongPlayerTurn);
@@ -308,19 +647,20 @@ impl GoBoard {
         let column = cell.column();
 
         let opponent = who.other();
-        let captures = self
-            .calculate_captures(cell, opponent)
-            .map(|c| (c.row(), c.column()))
-            .collect::<Vec<_>>();
+        let captures = self.calculate_captures(&cell, opponent);
 
-        if let Err(e) = self.check_ko(&captures) {
-            self.last_move = LastMove::IllegalKo;
+        if let Err(e) = self.check_suicidal(row, column, who, &captures) {
+            self.last_move = LastMove::IllegalSuicidal;
             self.board.set(row, column, GoCell::Empty);
             return Err(e);
         }
 
-        if let Err(e) = self.check_suicidal(row, column, &captures) {
-            self.last_move = LastMove::IllegalSuicidal;
+        let hash = captures.iter().fold(
+            self.hash ^ self.zobrist_key(row, column, who),
+            |hash, &(r, c)| hash ^ self.zobrist_key(r, c, opponent),
+        );
+        if let Err(e) = self.check_ko(hash, opponent) {
+            self.last_move = LastMove::IllegalKo;
             self.board.set(row, column, GoCell::Empty);
             return Err(e);
         }
@@ -334,6 +674,9 @@ impl GoBoard {
         self.board.set(row, column, played_cell);
 
         self.whos_turn = opponent;
+        self.last_move = LastMove::Ok;
+        self.hash = hash;
+        self.history.push((hash, opponent));
 
         Ok(())
     }
@@ -342,62 +685,78 @@ impl GoBoard {
      * It would probably be nicer if it allowed reading of K/V pairs in whatever order, put them in
      * a map and then we would look for specific keys
      */
-    fn read_kv<'a, 'b>(input: &'a str, name: &'b str) -> Result<&'a str, ParseError> {
-        let parts: Vec<&str> = input.split("=").collect();
+    fn read_kv<'a>((line, input): (usize, &'a str), name: &str) -> Result<&'a str, ParseError> {
+        let input = input.trim();
+        let parts: Vec<&str> = input.split('=').collect();
         match parts.len() {
-            2 => {
-                if parts[0] == name {
-                    Ok(parts[1])
-                } else {
-                    Err(ParseError::InvalidValue)
-                }
-            }
-            _ => Err(ParseError::InvalidValue),
+            2 if parts[0] == name => Ok(parts[1]),
+            _ => Err(ParseError::InvalidValue {
+                line,
+                found: input.to_string(),
+            }),
         }
     }
 
-    pub fn from_str(as_str: &str) -> Result<GoBoard, ParseError> {
-        let lines: Vec<&str> = as_str
-            .split("\n")
-            .map(|line| line.trim())
-            .filter(|line| !line.is_empty())
-            .collect::<Vec<&str>>();
+    fn parse_kv<V: FromStr>(line: (usize, &str), name: &str) -> Result<V, ParseError> {
+        let value = Self::read_kv(line, name)?;
+        value.parse().map_err(|_| ParseError::InvalidValue {
+            line: line.0,
+            found: value.to_string(),
+        })
+    }
 
-        if lines.len() <= 4 {
-            return Err(ParseError::NotEnoughLines);
-        }
+    fn liberties(region: &Region<GoCell>) -> impl Iterator<Item = Cell<GoCell>> + '_ {
+        region
+            .perimeter()
+            .iter()
+            .copied()
+            .filter(|c| c.value() == GoCell::Empty)
+    }
+}
 
-        let whos_turn = Self::read_kv(&lines[0], "turn")?;
-        let whos_turn: GoPlayer = match whos_turn.chars().nth(0) {
-            Some(c) => match GoPlayer::try_from(c) {
-                Ok(gp) => gp,
-                Err(e) => return Err(e),
-            },
-            None => return Err(ParseError::NotEnoughChars),
-        };
+impl FromStr for GoBoard {
+    type Err = ParseError;
 
-        let last_move = Self::read_kv(lines[1], "last_move")?;
-        let last_move: LastMove = match last_move.parse() {
-            Ok(i) => i,
-            Err(e) => return Err(e),
-        };
+    fn from_str(as_str: &str) -> Result<GoBoard, ParseError> {
+        let lines: Vec<(usize, &str)> = as_str
+            .split('\n')
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(_, line)| !line.trim().is_empty())
+            .collect();
 
-        let white_captures = Self::read_kv(lines[2], "capturesW")?;
-        let white_captures: u16 = match white_captures.parse() {
-            Ok(i) => i,
-            Err(_) => return Err(ParseError::InvalidValue),
-        };
+        if lines.len() <= 4 {
+            return Err(ParseError::NotEnoughLines {
+                expected: 5,
+                found: lines.len(),
+            });
+        }
 
-        let black_captures = Self::read_kv(lines[3], "capturesB")?;
-        let black_captures: u16 = match black_captures.parse() {
-            Ok(i) => i,
-            Err(_) => return Err(ParseError::InvalidValue),
+        let whos_turn = Self::read_kv(lines[0], "turn")?;
+        let column = lines[0].1.find('=').map_or(0, |i| i + 2);
+        let whos_turn: GoPlayer = match whos_turn.chars().next() {
+            Some(c) => GoPlayer::try_from(c).map_err(|_| ParseError::InvalidCharacter {
+                line: lines[0].0,
+                column,
+                found: c,
+            })?,
+            None => {
+                return Err(ParseError::NotEnoughChars {
+                    line: lines[0].0,
+                    column,
+                });
+            }
         };
 
-        let slice = &lines[4..];
-        let board: Arr2d<GoCell> = match Arr2d::from_lines(slice.iter().copied()) {
-            Ok(i) => i,
-            Err(e) => return Err(e),
+        let last_move: LastMove = Self::parse_kv(lines[1], "last_move")?;
+        let white_captures: u16 = Self::parse_kv(lines[2], "capturesW")?;
+        let black_captures: u16 = Self::parse_kv(lines[3], "capturesB")?;
+
+        let board_lines = lines[4..].iter().copied();
+        let board: Arr2d<GoCell> = if is_rle(lines[4].1) {
+            Self::rle_format().parse_lines(board_lines)?
+        } else {
+            Arr2d::from_numbered_lines(board_lines)?
         };
 
         let mut captures: HashMap<_, _> = HashMap::new();
@@ -405,39 +764,143 @@ impl GoBoard {
         captures.insert(GoPlayer::Black, black_captures);
 
         Ok(GoBoard {
-            whos_turn,
             last_move,
-            captures,
-            last_captures: VecDeque::new(),
-            board,
+            ..Self::from_parts(board, whos_turn, captures)
         })
     }
-
-    fn has_liberties(&self, row: usize, column: usize) -> Result<bool, &str> {
-        match self.get_liberties(row, column) {
-            Ok(c) => Ok(c.count() > 0),
-            Err(e) => Err(e),
-        }
-    }
-
-    fn get_liberties(
-        &self,
-        row: usize,
-        column: usize,
-    ) -> Result<impl Iterator<Item = &Cell<GoCell>>, &str> {
-        match self.board.get_perimeter(row, column) {
-            Ok(p) => Ok(p.filter(|c| c.value() == GoCell::Empty)),
-            _ => Err("Could not retrieve perimeter of {row}, {column}"),
-        }
-    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arr2d::testing;
+    use proptest::prelude::*;
     use std::fs;
     use test_case::test_case;
 
+    impl GoBoard {
+        /// Whether the group containing the given position has any empty points next to it.
+        fn has_liberties(&self, row: usize, column: usize) -> Result<bool, Arr2dError> {
+            Ok(self.get_liberties(row, column)?.next().is_some())
+        }
+
+        /// The empty points next to the group containing the given position.
+        fn get_liberties(
+            &self,
+            row: usize,
+            column: usize,
+        ) -> Result<impl Iterator<Item = Cell<GoCell>>, Arr2dError> {
+            self.board.try_get(row, column)?;
+            let regions = self.board.regions(Self::NEIGHBOURHOOD);
+            let liberties: Vec<Cell<GoCell>> = regions
+                .region_at(row, column)
+                .map(|region| Self::liberties(region).collect())
+                .unwrap_or_default();
+
+            Ok(liberties.into_iter())
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let state = create_go_from_test_file("ko/simple_1/2_execute.txt").unwrap();
+
+        let json = serde_json::to_string(&state).unwrap();
+        let result: GoBoard = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(result, state);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_rebuilds_hash() {
+        let state = create_go_from_test_file("ko/simple_1/2_execute.txt").unwrap();
+        let mut json = serde_json::to_value(&state).unwrap();
+        let saved = json.as_object_mut().unwrap();
+        // As saved before ko rules and hashing, then with a hash that does not match the stones
+        saved.remove("ko_rule");
+        let old_format: GoBoard = serde_json::from_value(json.clone()).unwrap();
+        json["hash"] = 1.into();
+        json["history"] = serde_json::json!([[1, "Black"]]);
+
+        let result: GoBoard = serde_json::from_value(json).unwrap();
+
+        assert_eq!(old_format, state);
+        assert_eq!(result, state);
+    }
+
+    #[test]
+    fn test_to_pgm() {
+        let state = create_go_from_test_file("ko/simple_1/2_execute.txt").unwrap();
+
+        let result = state.to_pgm(1).unwrap();
+
+        let header = format!(
+            "P5\n{} {}\n255\n",
+            state.board.width(),
+            state.board.height()
+        );
+        let (found_header, samples) = result.split_at(header.len());
+        assert_eq!(found_header, header.as_bytes());
+        let expected: Vec<u8> = state
+            .board
+            .all_cells()
+            .map(|c| match c.value() {
+                GoCell::Black | GoCell::BlackPending => 0,
+                GoCell::White | GoCell::WhitePending => 255,
+                GoCell::Empty => 181,
+            })
+            .collect();
+        assert_eq!(samples, expected);
+    }
+
+    fn go_cell() -> impl Strategy<Value = GoCell> + Clone {
+        prop_oneof![
+            Just(GoCell::Black),
+            Just(GoCell::White),
+            Just(GoCell::Empty)
+        ]
+    }
+
+    fn board_with_empty_point() -> impl Strategy<Value = (Arr2d<GoCell>, (usize, usize))> {
+        testing::arr2d_with_position(go_cell(), 7, 7).prop_map(|(mut board, (row, column))| {
+            board.set(row, column, GoCell::Empty);
+            (board, (row, column))
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn moves_leave_no_group_without_liberties(
+            (board, (row, column)) in board_with_empty_point(),
+            black_to_play: bool,
+        ) {
+            let whos_turn = if black_to_play { GoPlayer::Black } else { GoPlayer::White };
+            let mut state = GoBoard::from_parts(
+                board.clone(),
+                whos_turn,
+                HashMap::from([(GoPlayer::White, 0), (GoPlayer::Black, 0)]),
+            );
+
+            match state.make_move(row, column) {
+                Ok(()) => {
+                    let regions = state.board.regions(GoBoard::NEIGHBOURHOOD);
+                    let played = regions.region_at(row, column).unwrap();
+                    prop_assert_eq!(played.value(), whos_turn.into());
+                    prop_assert!(GoBoard::liberties(played).next().is_some());
+                    for neighbour in state.board.get_neighbours(row, column, GoBoard::NEIGHBOURHOOD) {
+                        if neighbour.value() == whos_turn.other().into() {
+                            let region = regions.region_at(neighbour.row(), neighbour.column()).unwrap();
+                            prop_assert!(GoBoard::liberties(region).next().is_some());
+                        }
+                    }
+                }
+                Err(_) => prop_assert_eq!(state.board, board),
+            }
+        }
+    }
+
     #[test_case(GoPlayer::White, GoPlayer::Black)]
     fn test_other_player(player: GoPlayer, expected: GoPlayer) {
         let result = player.other();
@@ -447,10 +910,8 @@ mod tests {
 
     fn test_file_raw_contents(basefile: &str) -> String {
         let filename = format!("resources/tests/go/{}", basefile);
-        fs::read_to_string(&filename).expect(&format!(
-            "Expected to find hardcoded test resource at {}",
-            filename
-        ))
+        fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Expected to find hardcoded test resource at {}", filename))
     }
 
     fn create_go_from_test_file(basefile: &str) -> Result<GoBoard, ParseError> {
@@ -462,9 +923,12 @@ mod tests {
         match parts.next() {
             Some(e) => match e.trim().parse() {
                 Ok(r) => Ok(r),
-                _ => return Err(ParseError::InvalidValue),
+                _ => Err(ParseError::InvalidValue {
+                    line: 1,
+                    found: e.to_string(),
+                }),
             },
-            None => return Err(ParseError::InvalidValue),
+            None => Err(ParseError::NotEnoughChars { line: 1, column: 1 }),
         }
     }
 
@@ -473,15 +937,9 @@ mod tests {
 
         let mut parts = file_contents.split(",");
 
-        let row = match extract_coord(&mut parts) {
-            Ok(r) => r,
-            Err(e) => return Err(e),
-        };
+        let row = extract_coord(&mut parts)?;
 
-        let column = match extract_coord(&mut parts) {
-            Ok(r) => r,
-            Err(e) => return Err(e),
-        };
+        let column = extract_coord(&mut parts)?;
 
         Ok((row, column))
     }
@@ -490,26 +948,141 @@ mod tests {
     fn test_ko_rule() {
         let mut state = create_go_from_test_file("ko/simple_1/1_before.txt").unwrap();
         let state_1_execute = create_go_from_test_file("ko/simple_1/1_execute.txt").unwrap();
-        let move_2 = create_move_from_test_file("ko/simple_1/2_move.txt").unwrap();
-        let state_2_execute = create_go_from_test_file("ko/simple_1/2_execute.txt").unwrap();
-        let move_3 = create_move_from_test_file("ko/simple_1/3_move.txt").unwrap();
-        let state_3_execute = create_go_from_test_file("ko/simple_1/3_execute.txt").unwrap();
-        let move_4 = create_move_from_test_file("ko/simple_1/4_move.txt").unwrap();
-        let state_4_execute = create_go_from_test_file("ko/simple_1/4_execute.txt").unwrap();
-
-        let _ = state.iterate().unwrap();
+
+        state.iterate().unwrap();
         assert_board_equal(&state_1_execute, &state);
 
-        let _ = state.make_move(move_2.0, move_2.1).unwrap();
-        assert_board_equal(&state_2_execute, &state);
+        // White takes the ko, black may not retake it until after a move elsewhere
+        for step in 2..=6 {
+            let (row, column) =
+                create_move_from_test_file(&format!("ko/simple_1/{step}_move.txt")).unwrap();
+            let expected =
+                create_go_from_test_file(&format!("ko/simple_1/{step}_execute.txt")).unwrap();
+
+            let result = state.make_move(row, column);
+
+            match step {
+                3 => assert_eq!(result, Err(GoBoardError::IllegalKo)),
+                _ => assert_eq!(result, Ok(()), "Expected move {step} to be legal"),
+            }
+            assert_board_equal(&expected, &state);
+        }
+    }
+
+    /// Plays the moves listed one per line in `ko/<name>/moves.txt`, as `row,column` or `pass`,
+    /// from `ko/<name>/before.txt`, returning the number of the first illegal move and its error, if any.
+    fn play_ko_fixture(name: &str, ko_rule: KoRule) -> Option<(usize, GoBoardError)> {
+        let mut state = create_go_from_test_file(&format!("ko/{name}/before.txt"))
+            .unwrap()
+            .with_ko_rule(ko_rule);
+        let moves = test_file_raw_contents(&format!("ko/{name}/moves.txt"));
+
+        for (number, line) in moves.lines().enumerate() {
+            let result = match line.trim() {
+                "pass" => {
+                    state.pass();
+                    Ok(())
+                }
+                point => {
+                    let mut parts = point.split(',');
+                    let row = extract_coord(&mut parts).unwrap();
+                    let column = extract_coord(&mut parts).unwrap();
+                    state.make_move(row, column)
+                }
+            };
+            if let Err(e) = result {
+                return Some((number + 1, e));
+            }
+        }
+        None
+    }
+
+    #[test_case("triple", KoRule::Simple, None)]
+    #[test_case("triple", KoRule::PositionalSuperko, Some(6))]
+    #[test_case("triple", KoRule::SituationalSuperko, Some(6))]
+    #[test_case("send_two_return_one", KoRule::Simple, None)]
+    #[test_case("send_two_return_one", KoRule::PositionalSuperko, Some(3))]
+    #[test_case("send_two_return_one", KoRule::SituationalSuperko, Some(5))]
+    fn test_superko(name: &str, ko_rule: KoRule, expected: Option<usize>) {
+        let result = play_ko_fixture(name, ko_rule);
+
+        assert_eq!(
+            result,
+            expected.map(|number| (number, GoBoardError::IllegalKo))
+        );
+    }
+
+    #[test]
+    fn test_zobrist_hash_depends_only_on_the_stones() {
+        let mut played = GoBoard::new(5);
+        played.make_move(0, 1).unwrap();
+        played.make_move(0, 0).unwrap();
+        played.make_move(1, 0).unwrap();
+        played.pass();
+
+        let result = GoBoard::from_str(
+            "turn=W\nlast_move=ok\ncapturesW=0\ncapturesB=1\n-B---\nB----\n-----\n-----\n-----",
+        )
+        .unwrap();
+
+        assert_eq!(played.board, result.board);
+        assert_eq!(played.zobrist_hash(), result.zobrist_hash());
+        assert_ne!(played.zobrist_hash(), GoBoard::new(5).zobrist_hash());
+    }
 
-        let _ = state.make_move(move_3.0, move_3.1).unwrap();
-        assert_board_equal(&state_3_execute, &state);
+    #[test]
+    fn test_move_off_board() {
+        let mut state = create_go_from_test_file("empty.txt").unwrap();
+        let before = create_go_from_test_file("empty.txt").unwrap();
 
-        let result4 = state.make_move(move_4.0, move_4.1);
+        let result = state.make_move(2, 5);
 
-        assert!(result4.is_err(), "Expected move 4 to generate an error");
-        assert_board_equal(&state_4_execute, &state);
+        assert_eq!(
+            result,
+            Err(GoBoardError::OffBoard(Arr2dError::OutOfBounds {
+                row: 2,
+                col: 5,
+                rows: 5,
+                cols: 5
+            }))
+        );
+        assert_board_equal(&before, &state);
+    }
+
+    #[test]
+    fn test_move_on_occupied_point() {
+        let mut state = create_go_from_test_file("parse/1.txt").unwrap();
+        let before = create_go_from_test_file("parse/1.txt").unwrap();
+
+        let result = state.make_move(2, 1);
+
+        assert_eq!(result, Err(GoBoardError::Occupied));
+        assert_board_equal(&before, &state);
+    }
+
+    #[test]
+    fn test_new_and_pass() {
+        let mut state = GoBoard::new(5);
+
+        state.pass();
+
+        assert_board_equal(&create_go_from_test_file("empty.txt").unwrap(), &state);
+    }
+
+    #[test_case(0.0, 10.0, "0", None)]
+    #[test_case(6.5, 16.5, "W+6.5", Some(GoPlayer::White))]
+    #[test_case(-2.0, 8.0, "B+2", Some(GoPlayer::Black))]
+    fn test_score(komi: f64, white: f64, expected: &str, winner: Option<GoPlayer>) {
+        let state = GoBoard::from_str(
+            "turn=B\nlast_move=ok\ncapturesW=0\ncapturesB=0\n-B-W-\n-B-W-\n-B-W-\n-B-W-\n-B-W-",
+        )
+        .unwrap();
+
+        let result = state.score(komi);
+
+        assert_eq!(result, Score { black: 10.0, white });
+        assert_eq!(result.to_string(), expected);
+        assert_eq!(result.winner(), winner);
     }
 
     #[test]
@@ -520,22 +1093,42 @@ mod tests {
 
         assert_eq!(
             state,
-            GoBoard {
-                whos_turn: GoPlayer::White,
-                last_move: LastMove::Ok,
-                last_captures: VecDeque::new(),
-                captures: [(GoPlayer::White, 16), (GoPlayer::Black, 23)]
-                    .iter()
-                    .cloned()
-                    .collect(),
-                board: Arr2d::from_contents(vec![
+            GoBoard::from_parts(
+                Arr2d::from_contents(vec![
                     vec![Empty, White, Empty, Empty, Empty,],
                     vec![Empty, Empty, White, Empty, Empty],
                     vec![Empty, Black, Empty, Empty, Empty],
                     vec![Empty, Black, Empty, BlackPending, Empty],
                     vec![Empty, Empty, Empty, Empty, Empty],
-                ])
-            }
+                ]),
+                GoPlayer::White,
+                HashMap::from([(GoPlayer::White, 16), (GoPlayer::Black, 23)]),
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_rle() {
+        let expected = create_go_from_test_file("parse/1.txt").unwrap();
+
+        let result = create_go_from_test_file("parse/1.rle").unwrap();
+
+        assert_eq!(result, expected);
+        assert_eq!(result.to_rle(), test_file_raw_contents("parse/1.rle"));
+    }
+
+    #[test]
+    fn test_parse_rle_error_lines() {
+        let result =
+            GoBoard::from_str("turn=W\nlast_move=ok\ncapturesW=0\ncapturesB=0\nx = 2, y = 2\n-Q!");
+
+        assert_eq!(
+            result,
+            Err(ParseError::InvalidCharacter {
+                line: 6,
+                column: 2,
+                found: 'Q'
+            })
         );
     }
 
@@ -558,25 +1151,31 @@ capturesB=23
 
         assert_eq!(
             state,
-            GoBoard {
-                whos_turn: GoPlayer::White,
-                last_move: LastMove::Ok,
-                last_captures: VecDeque::new(),
-                captures: [(GoPlayer::White, 16), (GoPlayer::Black, 23)]
-                    .iter()
-                    .cloned()
-                    .collect(),
-                board: Arr2d::from_contents(vec![
+            GoBoard::from_parts(
+                Arr2d::from_contents(vec![
                     vec![Empty, White, Empty, Empty, Empty,],
                     vec![Empty, Empty, White, Empty, Empty],
                     vec![Empty, Black, Empty, Empty, Empty],
                     vec![Empty, Black, Empty, BlackPending, Empty],
                     vec![Empty, Empty, Empty, Empty, Empty],
-                ])
-            }
+                ]),
+                GoPlayer::White,
+                HashMap::from([(GoPlayer::White, 16), (GoPlayer::Black, 23)]),
+            )
         );
     }
 
+    #[test_case("turn=X\nlast_move=ok\ncapturesW=0\ncapturesB=0\n---", ParseError::InvalidCharacter { line: 1, column: 6, found: 'X' })]
+    #[test_case("turn=W\nlast_move=ok\ncapturesW=0\ncapturesB=0\n---\n\n--", ParseError::RaggedRow { line: 7, expected: 3, found: 2 })]
+    #[test_case("turn=W\nlast_move=maybe\ncapturesW=0\ncapturesB=0\n---", ParseError::InvalidValue { line: 2, found: "maybe".to_string() })]
+    #[test_case("turn=W\nlast_move=ok\ncapturesB=0\ncapturesW=0\n---", ParseError::InvalidValue { line: 3, found: "capturesB=0".to_string() })]
+    #[test_case("turn=W\nlast_move=ok\ncapturesW=0\ncapturesB=0", ParseError::NotEnoughLines { expected: 5, found: 4 })]
+    fn test_parse_errors(input: &str, expected: ParseError) {
+        let result = GoBoard::from_str(input);
+
+        assert_eq!(result, Err(expected));
+    }
+
     #[test]
     fn test_has_liberties_true() {
         // Given
@@ -640,7 +1239,7 @@ WWWW-
         let state = GoBoard::from_str(as_str).unwrap();
 
         // When
-        let result: Vec<&Cell<GoCell>> = match state.get_liberties(2, 1) {
+        let result: Vec<Cell<GoCell>> = match state.get_liberties(2, 1) {
             Ok(p) => p.collect(),
             _ => panic!("Could not get liberties"),
         };
@@ -650,7 +1249,7 @@ WWWW-
             let exp_cell: Cell<GoCell> = Cell::from(exp_row, exp_column, GoCell::Empty);
 
             assert!(
-                result.contains(&&exp_cell),
+                result.contains(&exp_cell),
                 "Result {result:?} does not contain {exp_cell:?}"
             );
         }
@@ -684,9 +1283,13 @@ WWWW-
             expected.last_move, result.last_move,
             "Incorrect Last Move \n{expected}\n\n{result}"
         );
+        let diff = expected
+            .board
+            .render_diff(&result.board)
+            .unwrap_or_else(|e| e.to_string());
         assert_eq!(
             expected.board, result.board,
-            "Board states do not match \n{expected}\n\n{result}"
+            "Board states do not match (expected is old, result is new)\n{diff}"
         );
     }
 }
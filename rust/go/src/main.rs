@@ -1,5 +1,7 @@
+use go::{GoBoard, GoPlayer};
 use std::fmt;
-use std::io::{self, Write};
+use std::io::{self, BufRead, Write};
+use std::ops::RangeInclusive;
 use std::str::FromStr;
 
 #[derive(Debug, PartialEq)]
@@ -16,7 +18,7 @@ impl FromStr for Command {
     fn from_str(s: &str) -> Result<Command, Self::Err> {
         let parts: Vec<_> = s.split(" ").collect();
 
-        if let Some(base_command) = parts.get(0) {
+        if let Some(base_command) = parts.first() {
             match *base_command {
                 "place" => {
                     if let (Some(Ok(row)), Some(Ok(col))) = (
@@ -50,49 +52,170 @@ impl fmt::Display for Command {
     }
 }
 
-fn print_go_help() {
-    println!("Enter a command");
-    println!("---------------");
+/// The board size used when none is chosen.
+const DEFAULT_SIZE: usize = 9;
 
-    println!(
+/// The board sizes that can be chosen.
+const SIZES: RangeInclusive<usize> = 2..=25;
+
+/// Points given to white for moving second.
+const KOMI: f64 = 6.5;
+
+fn print_go_help(output: &mut impl Write) -> io::Result<()> {
+    writeln!(output, "Enter a command")?;
+    writeln!(output, "---------------")?;
+
+    writeln!(
+        output,
         "place row column => place a stone, e.g. {}",
         Command::PlaceStone(5, 6)
-    );
-    println!("{} => resign game, other player wins", Command::Resign);
-    println!("{} => pass", Command::Pass);
-    println!("{} => Quit the program", Command::Quit);
-    println!("---------------");
+    )?;
+    writeln!(
+        output,
+        "{} => resign game, other player wins",
+        Command::Resign
+    )?;
+    writeln!(
+        output,
+        "{} => pass, the game ends when both players pass",
+        Command::Pass
+    )?;
+    writeln!(output, "{} => Quit the program", Command::Quit)?;
+    writeln!(output, "---------------")
 }
 
-fn main() -> Result<(), io::Error> {
-    println!("Let's play go!");
-    print_go_help();
-    io::stdout().flush().expect("Failed to flush output");
+fn name(player: GoPlayer) -> &'static str {
+    match player {
+        GoPlayer::Black => "Black",
+        GoPlayer::White => "White",
+    }
+}
+
+/// The board with rows and columns numbered as `place` expects them, and the captures so far.
+fn render(board: &GoBoard, output: &mut impl Write) -> io::Result<()> {
+    let size = board.size();
+    write!(output, "  ")?;
+    for column in 0..size {
+        write!(output, "{column:>3}")?;
+    }
+    writeln!(output)?;
+    for row in 0..size {
+        write!(output, "{row:>2}")?;
+        for column in 0..size {
+            let point = match board.stone(row, column) {
+                Ok(Some(player)) => char::from(player),
+                _ => GoBoard::EMPTY,
+            };
+            write!(output, "{point:>3}")?;
+        }
+        writeln!(output)?;
+    }
+    writeln!(
+        output,
+        "Captured by black: {}, by white: {}",
+        board.captures(GoPlayer::Black),
+        board.captures(GoPlayer::White)
+    )
+}
 
+/// Asks for a board size until a valid one is given. An empty answer picks the default size, and
+/// `None` is returned if the input ends first.
+fn read_size(input: &mut impl BufRead, output: &mut impl Write) -> io::Result<Option<usize>> {
     loop {
-        let mut input = String::new();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Failed to read line");
-
-        if let Ok(cmd) = Command::from_str(input.trim()) {
-            match cmd {
-                Command::PlaceStone(r, c) => println!("Placing Stone {} {}", r, c),
-                Command::Resign => println!("Resigning"),
-                Command::Pass => println!("Passing"),
-                Command::Quit => {
-                    println!("Quit Game");
-                    break;
+        write!(output, "Board size ({DEFAULT_SIZE}): ")?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        match line.trim() {
+            "" => return Ok(Some(DEFAULT_SIZE)),
+            answer => match answer.parse() {
+                Ok(size) if SIZES.contains(&size) => return Ok(Some(size)),
+                _ => writeln!(
+                    output,
+                    "Board size must be between {} and {}",
+                    SIZES.start(),
+                    SIZES.end()
+                )?,
+            },
+        }
+    }
+}
+
+/// Plays a game reading commands from `input`, until both players pass, one resigns, or the
+/// input ends or asks to quit.
+fn play(mut input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    writeln!(output, "Let's play go!")?;
+    let Some(size) = read_size(&mut input, &mut output)? else {
+        return Ok(());
+    };
+    let mut board = GoBoard::new(size);
+    let mut passes = 0;
+
+    print_go_help(&mut output)?;
+    render(&board, &mut output)?;
+    loop {
+        let player = board.whos_turn();
+        write!(output, "{} to play: ", name(player))?;
+        output.flush()?;
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            writeln!(output)?;
+            return Ok(());
+        }
+
+        let Ok(cmd) = Command::from_str(line.trim()) else {
+            writeln!(output, "Failed to understand command")?;
+            print_go_help(&mut output)?;
+            continue;
+        };
+        match cmd {
+            Command::PlaceStone(r, c) => match board.make_move(r, c) {
+                Ok(()) => {
+                    passes = 0;
+                    render(&board, &mut output)?;
+                }
+                Err(e) => writeln!(output, "Illegal move: {e}")?,
+            },
+            Command::Pass => {
+                board.pass();
+                passes += 1;
+                writeln!(output, "{} passes", name(player))?;
+                if passes == 2 {
+                    let score = board.score(KOMI);
+                    writeln!(
+                        output,
+                        "Game over. Black has {} points, white has {} with komi",
+                        score.black, score.white
+                    )?;
+                    match score.winner() {
+                        Some(winner) => writeln!(output, "{} wins: {score}", name(winner))?,
+                        None => writeln!(output, "The game is a draw")?,
+                    }
+                    return Ok(());
                 }
             }
-        } else {
-            println!("Failed to understand command");
+            Command::Resign => {
+                writeln!(
+                    output,
+                    "{} resigns, {} wins",
+                    name(player),
+                    name(player.other())
+                )?;
+                return Ok(());
+            }
+            Command::Quit => {
+                writeln!(output, "Quit Game")?;
+                return Ok(());
+            }
         }
-
-        print_go_help();
     }
+}
 
-    Ok(())
+fn main() -> Result<(), io::Error> {
+    play(io::stdin().lock(), io::stdout().lock())
 }
 
 #[cfg(test)]
@@ -109,4 +232,92 @@ mod tests {
         assert_eq!(result, expected);
         assert_eq!(as_str, input);
     }
+
+    /// Plays a game from a script of input lines, returning everything written.
+    fn play_script(script: &str) -> String {
+        let mut output = Vec::new();
+        play(script.as_bytes(), &mut output).expect("Writing to a Vec should not fail");
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_render() {
+        let mut board = GoBoard::new(3);
+        board.make_move(1, 2).unwrap();
+        let mut output = Vec::new();
+
+        render(&board, &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "    0  1  2\n 0  -  -  -\n 1  -  -  B\n 2  -  -  -\nCaptured by black: 0, by white: 0\n"
+        );
+    }
+
+    #[test]
+    fn test_capture_and_score() {
+        let result = play_script("5\nplace 0 1\nplace 0 0\nplace 1 0\nplace 4 4\npass\npass\n");
+
+        assert!(result.contains("Captured by black: 1, by white: 0"));
+        assert!(result.contains("Black to play: Black passes\nWhite to play: White passes\n"));
+        assert!(result.contains("Game over. Black has 3 points, white has 7.5 with komi"));
+        assert!(result.ends_with("White wins: W+4.5\n"));
+    }
+
+    #[test]
+    fn test_illegal_moves_are_reported() {
+        let result = play_script(
+            "5\nplace 0 1\nplace 4 4\nplace 1 0\nplace 0 0\nplace 0 1\nplace 5 0\nquit\n",
+        );
+
+        assert!(result.contains(
+            "White to play: Illegal move: the move would leave its own group without liberties\n"
+        ));
+        assert!(result.contains("White to play: Illegal move: the point already holds a stone\n"));
+        assert!(
+            result.contains(
+                "White to play: Illegal move: position (5, 0) is outside of a 5x5 grid\n"
+            )
+        );
+        assert!(result.ends_with("White to play: Quit Game\n"));
+    }
+
+    #[test]
+    fn test_ko_is_reported() {
+        // White takes the ko, and black cannot take it straight back
+        let script = "5\nplace 0 0\nplace 0 3\nplace 1 1\nplace 1 2\nplace 1 3\nplace 4 4\nplace 0 2\nplace 0 1\nplace 0 2\nquit\n";
+
+        let result = play_script(script);
+
+        assert!(result.contains("Black to play: Illegal move: the move retakes a ko\n"));
+    }
+
+    #[test]
+    fn test_resign_names_the_winner() {
+        let result = play_script("\nplace 4 4\nresign\n");
+
+        assert!(result.contains(&format!("  {}\n", "  0  1  2  3  4  5  6  7  8")));
+        assert!(result.ends_with("White to play: White resigns, Black wins\n"));
+    }
+
+    #[test]
+    fn test_size_is_checked() {
+        let result = play_script("1\nbig\n3\nquit\n");
+
+        assert_eq!(
+            result
+                .matches("Board size must be between 2 and 25")
+                .count(),
+            2
+        );
+        assert!(result.contains(" 2  -  -  -\n"));
+    }
+
+    #[test]
+    fn test_unknown_command_and_end_of_input() {
+        let result = play_script("5\nfly\n");
+
+        assert!(result.contains("Failed to understand command\nEnter a command"));
+        assert!(result.ends_with("Black to play: \n"));
+    }
 }
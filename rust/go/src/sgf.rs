@@ -0,0 +1,950 @@
+//! Reading and writing games in the Smart Game Format, [FF\[4\]](https://www.red-bean.com/sgf/),
+//! which most Go software uses to record games.
+//!
+//! [`SgfTree`] holds the nodes and properties of any SGF file as text, while [`GameRecord`]
+//! reads the main line of a Go game from one: the board size, komi, handicap, game information,
+//! set up stones, moves and comments. Other properties, such as board markup, are dropped.
+
+use crate::{GoBoard, GoBoardError, GoPlayer};
+use std::fmt;
+use std::iter::Peekable;
+use std::ops::RangeInclusive;
+use std::str::{Chars, FromStr};
+
+/// The board size to assume when a game does not give one.
+const DEFAULT_SIZE: usize = 19;
+
+/// Board sizes that points can be written for, with a letter from `a` to `z` and then `A` to
+/// `Z` along each side.
+const SIZES: RangeInclusive<usize> = 1..=52;
+
+/// Boards of up to this size may also write a pass as a move to `tt`.
+const OLD_PASS_SIZE: usize = 19;
+
+#[derive(Debug, PartialEq)]
+pub enum SgfError {
+    UnexpectedEnd,
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+    /// The file records a game other than Go, given by its `GM` number.
+    UnsupportedGame(String),
+    InvalidProperty {
+        property: String,
+        value: String,
+    },
+    /// A move in the main line that the rules do not allow, counting moves from 1.
+    IllegalMove {
+        move_number: usize,
+        error: GoBoardError,
+    },
+}
+
+impl fmt::Display for SgfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SgfError::UnexpectedEnd => write!(f, "unexpected end of file"),
+            SgfError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "line {line}, column {column}: unexpected character {found:?}"
+            ),
+            SgfError::UnsupportedGame(game) => write!(f, "game type {game} is not Go"),
+            SgfError::InvalidProperty { property, value } => {
+                write!(f, "invalid value {value:?} for property {property}")
+            }
+            SgfError::IllegalMove { move_number, error } => {
+                write!(f, "move {move_number} is illegal: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SgfError {}
+
+/// A node of an SGF file, holding its properties in the order they were given.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SgfNode {
+    properties: Vec<(String, Vec<String>)>,
+}
+
+impl SgfNode {
+    pub fn new() -> SgfNode {
+        SgfNode::default()
+    }
+
+    /// The first value of a property, if the node has it.
+    pub fn get(&self, property: &str) -> Option<&str> {
+        self.values(property).first().map(String::as_str)
+    }
+
+    /// Every value of a property, which is empty if the node does not have it.
+    pub fn values(&self, property: &str) -> &[String] {
+        self.properties
+            .iter()
+            .find(|(id, _)| id == property)
+            .map_or(&[], |(_, values)| values.as_slice())
+    }
+
+    /// Replaces the values of a property, adding it after the others if the node did not have it.
+    pub fn set(&mut self, property: &str, values: Vec<String>) {
+        match self.properties.iter_mut().find(|(id, _)| id == property) {
+            Some((_, existing)) => *existing = values,
+            None => self.properties.push((property.to_string(), values)),
+        }
+    }
+
+    pub fn properties(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.properties
+            .iter()
+            .map(|(id, values)| (id.as_str(), values.as_slice()))
+    }
+}
+
+impl fmt::Display for SgfNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, ";")?;
+        for (id, values) in &self.properties {
+            write!(f, "{id}")?;
+            for value in values {
+                write!(f, "[{}]", escape(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A sequence of nodes followed by any number of variations. The first variation continues the
+/// main line.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SgfTree {
+    pub nodes: Vec<SgfNode>,
+    pub variations: Vec<SgfTree>,
+}
+
+impl SgfTree {
+    /// Reads every game tree in a file.
+    pub fn parse_collection(as_str: &str) -> Result<Vec<SgfTree>, SgfError> {
+        let mut parser = Parser::new(as_str);
+        let mut trees = Vec::new();
+        parser.skip_whitespace();
+        while parser.peek().is_some() {
+            trees.push(parser.tree()?);
+            parser.skip_whitespace();
+        }
+        Ok(trees)
+    }
+
+    /// The nodes from the root to the end of the first variation at each branch.
+    pub fn main_line(&self) -> impl Iterator<Item = &SgfNode> {
+        let mut tree = Some(self);
+        std::iter::from_fn(move || {
+            let current = tree?;
+            tree = current.variations.first();
+            Some(current.nodes.iter())
+        })
+        .flatten()
+    }
+}
+
+/// Reads the first game tree in a file.
+impl FromStr for SgfTree {
+    type Err = SgfError;
+
+    fn from_str(as_str: &str) -> Result<SgfTree, SgfError> {
+        let mut parser = Parser::new(as_str);
+        parser.skip_whitespace();
+        parser.tree()
+    }
+}
+
+/// Writes each node on its own line. The variations still to write are kept on a stack rather
+/// than recursing, so deeply nested variations cannot overflow the call stack.
+impl fmt::Display for SgfTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut open: Vec<std::slice::Iter<SgfTree>> = Vec::new();
+        let mut next = Some(self);
+        loop {
+            if let Some(tree) = next.take() {
+                write!(f, "(")?;
+                for (i, node) in tree.nodes.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{node}")?;
+                }
+                open.push(tree.variations.iter());
+            }
+
+            let Some(variations) = open.last_mut() else {
+                return Ok(());
+            };
+            match variations.next() {
+                Some(variation) => {
+                    writeln!(f)?;
+                    next = Some(variation);
+                }
+                None => {
+                    open.pop();
+                    write!(f, ")")?;
+                }
+            }
+        }
+    }
+}
+
+/// Drops nested variations one at a time, as dropping them recursively could overflow the
+/// call stack.
+impl Drop for SgfTree {
+    fn drop(&mut self) {
+        let mut variations = std::mem::take(&mut self.variations);
+        while let Some(mut variation) = variations.pop() {
+            variations.append(&mut variation.variations);
+        }
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+    column: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(as_str: &'a str) -> Parser<'a> {
+        Parser {
+            chars: as_str.chars().peekable(),
+            line: 1,
+            column: 1,
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    fn next(&mut self) -> Result<char, SgfError> {
+        let c = self.chars.next().ok_or(SgfError::UnexpectedEnd)?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Ok(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            let _ = self.next();
+        }
+    }
+
+    fn unexpected(&mut self) -> SgfError {
+        match self.peek() {
+            Some(found) => SgfError::UnexpectedChar {
+                line: self.line,
+                column: self.column,
+                found,
+            },
+            None => SgfError::UnexpectedEnd,
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), SgfError> {
+        match self.peek() {
+            Some(c) if c == expected => self.next().map(|_| ()),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    /// Reads a game tree with its variations. The trees still open are kept on a stack rather
+    /// than recursing, so deeply nested variations cannot overflow the call stack.
+    fn tree(&mut self) -> Result<SgfTree, SgfError> {
+        let mut open: Vec<SgfTree> = Vec::new();
+        loop {
+            self.expect('(')?;
+            self.skip_whitespace();
+
+            let mut tree = SgfTree::default();
+            while self.peek() == Some(';') {
+                self.next()?;
+                tree.nodes.push(self.node()?);
+            }
+            if tree.nodes.is_empty() {
+                return Err(self.unexpected());
+            }
+            open.push(tree);
+
+            // Close trees until one has another variation to read
+            while self.peek() != Some('(') {
+                self.expect(')')?;
+                let done = open.pop().expect("A tree should be open");
+                match open.last_mut() {
+                    Some(parent) => parent.variations.push(done),
+                    None => return Ok(done),
+                }
+                self.skip_whitespace();
+            }
+        }
+    }
+
+    fn node(&mut self) -> Result<SgfNode, SgfError> {
+        let mut node = SgfNode::new();
+        loop {
+            self.skip_whitespace();
+            let mut id = String::new();
+            while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+                self.next()?;
+                // Older versions allowed lower case letters in identifiers, which are ignored
+                if c.is_ascii_uppercase() {
+                    id.push(c);
+                }
+            }
+            if id.is_empty() {
+                return Ok(node);
+            }
+
+            self.skip_whitespace();
+            let mut values = Vec::new();
+            while self.peek() == Some('[') {
+                self.next()?;
+                values.push(self.value()?);
+                self.skip_whitespace();
+            }
+            if values.is_empty() {
+                return Err(self.unexpected());
+            }
+            node.properties.push((id, values));
+        }
+    }
+
+    /// Reads a value up to its closing bracket, resolving escapes. An escaped line break is a soft
+    /// line break, which is removed.
+    fn value(&mut self) -> Result<String, SgfError> {
+        let mut value = String::new();
+        loop {
+            match self.next()? {
+                ']' => return Ok(value),
+                '\\' => match self.next()? {
+                    '\n' => {
+                        if self.peek() == Some('\r') {
+                            self.next()?;
+                        }
+                    }
+                    '\r' => {
+                        if self.peek() == Some('\n') {
+                            self.next()?;
+                        }
+                    }
+                    c => value.push(c),
+                },
+                c => value.push(c),
+            }
+        }
+    }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace(']', "\\]")
+}
+
+/// A turn in the game, either placing a stone at a row and column or passing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    Play(usize, usize),
+    Pass,
+}
+
+/// Stones added to or removed from the board outside of play, such as handicap stones.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Setup {
+    pub black: Vec<(usize, usize)>,
+    pub white: Vec<(usize, usize)>,
+    pub empty: Vec<(usize, usize)>,
+}
+
+impl Setup {
+    pub fn is_empty(&self) -> bool {
+        self.black.is_empty() && self.white.is_empty() && self.empty.is_empty()
+    }
+}
+
+/// One node of the main line of a game.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GameNode {
+    pub setup: Setup,
+    /// Who plays next, when the set up stones change whose turn it would otherwise be.
+    pub to_play: Option<GoPlayer>,
+    pub play: Option<(GoPlayer, Move)>,
+    pub comment: Option<String>,
+}
+
+/// Information about a game from the root node, such as who played it and the result.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct GameInfo {
+    pub name: Option<String>,
+    pub event: Option<String>,
+    pub round: Option<String>,
+    pub date: Option<String>,
+    pub place: Option<String>,
+    pub black_player: Option<String>,
+    pub black_rank: Option<String>,
+    pub white_player: Option<String>,
+    pub white_rank: Option<String>,
+    pub rules: Option<String>,
+    pub result: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl GameInfo {
+    /// Each field with the property it is stored in, in the order they are written.
+    fn fields_mut(&mut self) -> [(&'static str, &mut Option<String>); 12] {
+        [
+            ("GN", &mut self.name),
+            ("EV", &mut self.event),
+            ("RO", &mut self.round),
+            ("DT", &mut self.date),
+            ("PC", &mut self.place),
+            ("PB", &mut self.black_player),
+            ("BR", &mut self.black_rank),
+            ("PW", &mut self.white_player),
+            ("WR", &mut self.white_rank),
+            ("RU", &mut self.rules),
+            ("RE", &mut self.result),
+            ("GC", &mut self.comment),
+        ]
+    }
+}
+
+/// The main line of a recorded game of Go.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRecord {
+    pub size: usize,
+    pub komi: f64,
+    pub handicap: usize,
+    pub info: GameInfo,
+    /// The nodes of the main line, starting with the root.
+    pub nodes: Vec<GameNode>,
+}
+
+impl GameRecord {
+    /// A game on an empty board of the given size, with no moves yet.
+    pub fn new(size: usize) -> GameRecord {
+        GameRecord {
+            size,
+            komi: 0.0,
+            handicap: 0,
+            info: GameInfo::default(),
+            nodes: vec![GameNode::default()],
+        }
+    }
+
+    /// Records a game by playing the moves in turn, starting with black, and stops at the first
+    /// move that breaks the rules.
+    pub fn from_moves(
+        size: usize,
+        moves: impl IntoIterator<Item = Move>,
+    ) -> Result<GameRecord, SgfError> {
+        let mut record = GameRecord::new(size);
+        let mut board = GoBoard::new(size);
+        for (i, play) in moves.into_iter().enumerate() {
+            let player = board.whos_turn;
+            Self::play(&mut board, player, play, i + 1)?;
+            record.nodes.push(GameNode {
+                play: Some((player, play)),
+                ..GameNode::default()
+            });
+        }
+        Ok(record)
+    }
+
+    /// Reads the main line of a game tree.
+    pub fn from_tree(tree: &SgfTree) -> Result<GameRecord, SgfError> {
+        let mut nodes = tree.main_line();
+        let root = nodes.next().ok_or(SgfError::UnexpectedEnd)?;
+
+        let mut record = Self::read_header(root)?;
+        record.nodes = std::iter::once(root)
+            .chain(nodes)
+            .map(|node| Self::read_node(node, record.size))
+            .collect::<Result<_, _>>()?;
+        Ok(record)
+    }
+
+    pub fn to_tree(&self) -> SgfTree {
+        let mut root = self.write_header();
+        let mut nodes: Vec<SgfNode> = self.nodes.iter().map(Self::write_node).collect();
+        match nodes.first_mut() {
+            Some(first) => {
+                root.properties.append(&mut first.properties);
+                nodes[0] = root;
+            }
+            None => nodes.push(root),
+        }
+
+        SgfTree {
+            nodes,
+            variations: Vec::new(),
+        }
+    }
+
+    pub fn to_sgf(&self) -> String {
+        format!("{}\n", self.to_tree())
+    }
+
+    /// Plays the main line out on an empty board, checking each move against the rules, and
+    /// returns the final position.
+    ///
+    /// Moves are played by the colour the record gives them, even when that player has just
+    /// moved, as happens after handicap stones are placed.
+    pub fn replay(&self) -> Result<GoBoard, SgfError> {
+        let mut board = GoBoard::new(self.size);
+        let mut move_number = 0;
+        for node in &self.nodes {
+            if node.play.is_some() {
+                move_number += 1;
+            }
+            Self::apply_node(&mut board, node, move_number)?;
+        }
+        Ok(board)
+    }
+
+    /// Reads the size, komi, handicap and game information from a root node, leaving the record
+    /// without any nodes.
+    pub(crate) fn read_header(root: &SgfNode) -> Result<GameRecord, SgfError> {
+        if let Some(game) = root.get("GM").filter(|&game| game.trim() != "1") {
+            return Err(SgfError::UnsupportedGame(game.to_string()));
+        }
+        let size = match root.get("SZ") {
+            Some(value) => match parse_number("SZ", value)? {
+                size if SIZES.contains(&size) => size,
+                _ => {
+                    return Err(SgfError::InvalidProperty {
+                        property: "SZ".to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            },
+            None => DEFAULT_SIZE,
+        };
+        let komi = match root.get("KM") {
+            Some(value) if !value.trim().is_empty() => parse_number("KM", value)?,
+            _ => 0.0,
+        };
+        let handicap = match root.get("HA") {
+            Some(value) => parse_number("HA", value)?,
+            None => 0,
+        };
+
+        let mut info = GameInfo::default();
+        for (id, field) in info.fields_mut() {
+            *field = root.get(id).map(str::to_string);
+        }
+
+        Ok(GameRecord {
+            size,
+            komi,
+            handicap,
+            info,
+            nodes: Vec::new(),
+        })
+    }
+
+    /// A root node holding the size, komi, handicap and game information.
+    pub(crate) fn write_header(&self) -> SgfNode {
+        let mut root = SgfNode::new();
+        root.set("FF", vec!["4".to_string()]);
+        root.set("GM", vec!["1".to_string()]);
+        root.set("CA", vec!["UTF-8".to_string()]);
+        root.set("SZ", vec![self.size.to_string()]);
+        root.set("KM", vec![self.komi.to_string()]);
+        if self.handicap > 0 {
+            root.set("HA", vec![self.handicap.to_string()]);
+        }
+        for (id, field) in self.info.clone().fields_mut() {
+            if let Some(value) = field.take() {
+                root.set(id, vec![value]);
+            }
+        }
+        root
+    }
+
+    /// Places a node's set up stones then plays its move, reporting a broken rule as the given
+    /// move number.
+    pub(crate) fn apply_node(
+        board: &mut GoBoard,
+        node: &GameNode,
+        move_number: usize,
+    ) -> Result<(), SgfError> {
+        for (stones, stone) in [
+            (&node.setup.black, Some(GoPlayer::Black)),
+            (&node.setup.white, Some(GoPlayer::White)),
+            (&node.setup.empty, None),
+        ] {
+            for &(row, column) in stones {
+                board.set_stone(row, column, stone);
+            }
+        }
+        if let Some(player) = node.to_play {
+            board.whos_turn = player;
+        }
+        match node.play {
+            Some((player, play)) => Self::play(board, player, play, move_number),
+            None => Ok(()),
+        }
+    }
+
+    fn play(
+        board: &mut GoBoard,
+        player: GoPlayer,
+        play: Move,
+        move_number: usize,
+    ) -> Result<(), SgfError> {
+        board.whos_turn = player;
+        match play {
+            Move::Play(row, column) => board
+                .make_move(row, column)
+                .map_err(|error| SgfError::IllegalMove { move_number, error }),
+            Move::Pass => {
+                board.pass();
+                Ok(())
+            }
+        }
+    }
+
+    pub(crate) fn read_node(node: &SgfNode, size: usize) -> Result<GameNode, SgfError> {
+        let black = node.get("B").map(|value| (GoPlayer::Black, value, "B"));
+        let white = node.get("W").map(|value| (GoPlayer::White, value, "W"));
+        let play = match (black, white) {
+            (Some(_), Some(_)) => {
+                return Err(SgfError::InvalidProperty {
+                    property: "W".to_string(),
+                    value: node.get("W").unwrap_or_default().to_string(),
+                });
+            }
+            (Some((player, value, id)), None) | (None, Some((player, value, id))) => {
+                Some((player, parse_move(id, value, size)?))
+            }
+            (None, None) => None,
+        };
+
+        let to_play = match node.get("PL") {
+            Some("B") => Some(GoPlayer::Black),
+            Some("W") => Some(GoPlayer::White),
+            Some(value) => {
+                return Err(SgfError::InvalidProperty {
+                    property: "PL".to_string(),
+                    value: value.to_string(),
+                });
+            }
+            None => None,
+        };
+
+        Ok(GameNode {
+            setup: Setup {
+                black: parse_points("AB", node.values("AB"), size)?,
+                white: parse_points("AW", node.values("AW"), size)?,
+                empty: parse_points("AE", node.values("AE"), size)?,
+            },
+            to_play,
+            play,
+            comment: node.get("C").map(str::to_string),
+        })
+    }
+
+    pub(crate) fn write_node(node: &GameNode) -> SgfNode {
+        let mut written = SgfNode::new();
+        for (id, stones) in [
+            ("AB", &node.setup.black),
+            ("AW", &node.setup.white),
+            ("AE", &node.setup.empty),
+        ] {
+            if !stones.is_empty() {
+                written.set(id, stones.iter().map(|&point| write_point(point)).collect());
+            }
+        }
+        if let Some(player) = node.to_play {
+            written.set("PL", vec![player.to_string()]);
+        }
+        if let Some((player, play)) = node.play {
+            let value = match play {
+                Move::Play(row, column) => write_point((row, column)),
+                Move::Pass => String::new(),
+            };
+            written.set(&player.to_string(), vec![value]);
+        }
+        if let Some(comment) = &node.comment {
+            written.set("C", vec![comment.clone()]);
+        }
+        written
+    }
+}
+
+/// Reads the first game in a file.
+impl FromStr for GameRecord {
+    type Err = SgfError;
+
+    fn from_str(as_str: &str) -> Result<GameRecord, SgfError> {
+        GameRecord::from_tree(&SgfTree::from_str(as_str)?)
+    }
+}
+
+fn parse_number<N: FromStr>(property: &str, value: &str) -> Result<N, SgfError> {
+    value.trim().parse().map_err(|_| SgfError::InvalidProperty {
+        property: property.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn parse_move(property: &str, value: &str, size: usize) -> Result<Move, SgfError> {
+    match value {
+        "" => Ok(Move::Pass),
+        "tt" if size <= OLD_PASS_SIZE => Ok(Move::Pass),
+        _ => parse_point(property, value, size).map(|(row, column)| Move::Play(row, column)),
+    }
+}
+
+/// Reads a list of points, where `aa:cc` stands for every point in the rectangle between the two.
+fn parse_points(
+    property: &str,
+    values: &[String],
+    size: usize,
+) -> Result<Vec<(usize, usize)>, SgfError> {
+    let mut points = Vec::new();
+    for value in values {
+        match value.split_once(':') {
+            Some((first, last)) => {
+                let (first_row, first_column) = parse_point(property, first, size)?;
+                let (last_row, last_column) = parse_point(property, last, size)?;
+                for row in first_row.min(last_row)..=first_row.max(last_row) {
+                    for column in first_column.min(last_column)..=first_column.max(last_column) {
+                        points.push((row, column));
+                    }
+                }
+            }
+            None => points.push(parse_point(property, value, size)?),
+        }
+    }
+    Ok(points)
+}
+
+/// Reads a point written as its column then its row, counting from `a` and on to `A` after `z`.
+fn parse_point(property: &str, value: &str, size: usize) -> Result<(usize, usize), SgfError> {
+    let invalid = || SgfError::InvalidProperty {
+        property: property.to_string(),
+        value: value.to_string(),
+    };
+    let mut chars = value.chars().map(|c| match c {
+        'a'..='z' => Some(c as usize - 'a' as usize),
+        'A'..='Z' => Some(c as usize - 'A' as usize + 26),
+        _ => None,
+    });
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(Some(column)), Some(Some(row)), None) if row < size && column < size => {
+            Ok((row, column))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn write_point((row, column): (usize, usize)) -> String {
+    [column, row]
+        .into_iter()
+        .map(|i| match i {
+            0..26 => (b'a' + i as u8) as char,
+            _ => (b'A' + (i - 26) as u8) as char,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use test_case::test_case;
+
+    fn fixture(name: &str) -> String {
+        let filename = format!("resources/tests/sgf/{name}.sgf");
+        fs::read_to_string(&filename)
+            .unwrap_or_else(|_| panic!("Expected to find hardcoded test resource at {filename}"))
+    }
+
+    fn record(name: &str) -> GameRecord {
+        GameRecord::from_str(&fixture(name)).expect("Fixture should have parsed")
+    }
+
+    #[test]
+    fn test_parse_game_info() {
+        let result = record("simple");
+
+        assert_eq!(result.size, 9);
+        assert_eq!(result.komi, 6.5);
+        assert_eq!(result.handicap, 0);
+        assert_eq!(result.info.name.as_deref(), Some("Fixture game"));
+        assert_eq!(result.info.black_player.as_deref(), Some("Black Player"));
+        assert_eq!(result.info.white_rank.as_deref(), Some("2k"));
+        assert_eq!(result.info.result.as_deref(), Some("B+3.5"));
+        assert_eq!(result.info.event, None);
+        assert_eq!(
+            result.nodes[0].comment.as_deref(),
+            Some("A short game with a capture.")
+        );
+        assert_eq!(result.nodes.len(), 10);
+        assert_eq!(
+            result.nodes[1].play,
+            Some((GoPlayer::Black, Move::Play(2, 2)))
+        );
+        assert_eq!(result.nodes[8].play, Some((GoPlayer::White, Move::Pass)));
+        assert_eq!(result.nodes[9].play, Some((GoPlayer::Black, Move::Pass)));
+    }
+
+    #[test_case(
+        "simple",
+        "---------\n---B-----\n--B-B----\n---B-----\n---------\n---------\n--W---W--\n---------\n---------\n",
+        GoPlayer::White
+    )]
+    #[test_case(
+        "handicap",
+        "---------\n---------\n--B-B----\n---------\n--W-W----\n---------\n------B--\n---------\n---------\n",
+        GoPlayer::Black
+    )]
+    #[test_case("setup", "-BW--\nWW---\n--B--\n---W-\n-----\n", GoPlayer::Black)]
+    #[test_case("variations", "-----\n-W---\n--B--\n---B-\n-----\n", GoPlayer::White)]
+    fn test_replay(name: &str, expected: &str, to_play: GoPlayer) {
+        let result = record(name).replay().unwrap();
+
+        assert_eq!(result.board.to_str(), expected);
+        assert_eq!(result.whos_turn, to_play);
+    }
+
+    #[test]
+    fn test_replay_counts_captures() {
+        let result = record("simple").replay().unwrap();
+
+        assert_eq!(result.captures[&GoPlayer::Black], 1);
+        assert_eq!(result.captures[&GoPlayer::White], 0);
+    }
+
+    #[test_case("illegal_ko", 3, GoBoardError::IllegalKo)]
+    #[test_case("illegal_suicide", 1, GoBoardError::IllegalSuicide)]
+    #[test_case("illegal_occupied", 2, GoBoardError::Occupied)]
+    fn test_replay_illegal_moves(name: &str, move_number: usize, error: GoBoardError) {
+        let result = record(name).replay();
+
+        assert_eq!(result, Err(SgfError::IllegalMove { move_number, error }));
+    }
+
+    #[test]
+    fn test_escapes() {
+        let result = record("escapes");
+
+        assert_eq!(
+            result.nodes[0].comment.as_deref(),
+            Some("A comment with a ] bracket, a back\\slash and a soft line break.")
+        );
+        assert_eq!(
+            result.nodes[1].comment.as_deref(),
+            Some("Line one\nline two")
+        );
+        assert!(result.to_sgf().contains(r"a \] bracket, a back\\slash"));
+    }
+
+    #[test]
+    fn test_round_trip_fixtures() {
+        for entry in fs::read_dir("resources/tests/sgf").unwrap() {
+            let path = entry.unwrap().path();
+            let input = GameRecord::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+            let result = GameRecord::from_str(&input.to_sgf());
+
+            assert_eq!(result, Ok(input), "{} did not round trip", path.display());
+        }
+    }
+
+    #[test]
+    fn test_deeply_nested_variations() {
+        let depth = 200_000;
+        let input = format!("{}{}", vec!["(;C[x]"; depth].join("\n"), ")".repeat(depth));
+
+        let result = SgfTree::from_str(&input).unwrap();
+
+        assert_eq!(result.main_line().count(), depth);
+        assert_eq!(result.to_string(), input);
+    }
+
+    #[test]
+    fn test_tree_keeps_variations() {
+        let input = SgfTree::from_str(&fixture("variations")).unwrap();
+
+        let result = SgfTree::from_str(&input.to_string()).unwrap();
+
+        assert_eq!(result, input);
+        assert_eq!(result.variations.len(), 2);
+        assert_eq!(
+            result.variations[1].nodes[0].get("C"),
+            Some("Not the main line")
+        );
+        let main_line: Vec<Option<&str>> = result.main_line().map(|n| n.get("B")).collect();
+        assert_eq!(main_line, [None, Some("cc"), None, Some("dd")]);
+    }
+
+    #[test]
+    fn test_parse_collection() {
+        let input = format!("{}\n{}", fixture("simple"), fixture("setup"));
+
+        let result = SgfTree::parse_collection(&input).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[1].nodes[0].get("SZ"), Some("5"));
+    }
+
+    #[test]
+    fn test_from_moves() {
+        let moves = [Move::Play(2, 2), Move::Pass, Move::Play(0, 1)];
+
+        let result = GameRecord::from_moves(5, moves).unwrap();
+
+        assert_eq!(
+            result.to_sgf(),
+            "(;FF[4]GM[1]CA[UTF-8]SZ[5]KM[0]\n;B[cc]\n;W[]\n;B[ba])\n"
+        );
+        let board = result.replay().unwrap();
+        assert_eq!(board.board.to_str(), "-B---\n-----\n--B--\n-----\n-----\n");
+    }
+
+    #[test]
+    fn test_from_moves_stops_at_illegal_move() {
+        let moves = [Move::Play(2, 2), Move::Play(2, 2)];
+
+        let result = GameRecord::from_moves(5, moves);
+
+        assert_eq!(
+            result,
+            Err(SgfError::IllegalMove {
+                move_number: 2,
+                error: GoBoardError::Occupied
+            })
+        );
+    }
+
+    #[test_case("(;B[aa]", SgfError::UnexpectedEnd)]
+    #[test_case(";B[aa])", SgfError::UnexpectedChar { line: 1, column: 1, found: ';' })]
+    #[test_case("(;SZ[5]\n;B)", SgfError::UnexpectedChar { line: 2, column: 3, found: ')' })]
+    #[test_case("()", SgfError::UnexpectedChar { line: 1, column: 2, found: ')' })]
+    #[test_case("(;GM[6])", SgfError::UnsupportedGame("6".to_string()))]
+    #[test_case("(;SZ[nine])", SgfError::InvalidProperty { property: "SZ".to_string(), value: "nine".to_string() })]
+    #[test_case("(;SZ[0])", SgfError::InvalidProperty { property: "SZ".to_string(), value: "0".to_string() })]
+    #[test_case("(;SZ[100000])", SgfError::InvalidProperty { property: "SZ".to_string(), value: "100000".to_string() })]
+    #[test_case("(;SZ[5];B[ff])", SgfError::InvalidProperty { property: "B".to_string(), value: "ff".to_string() })]
+    #[test_case("(;B[aa]W[bb])", SgfError::InvalidProperty { property: "W".to_string(), value: "bb".to_string() })]
+    #[test_case("(;PL[X])", SgfError::InvalidProperty { property: "PL".to_string(), value: "X".to_string() })]
+    fn test_parse_errors(input: &str, expected: SgfError) {
+        assert_eq!(GameRecord::from_str(input), Err(expected));
+    }
+}
@@ -0,0 +1,137 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Runs the engine binary on a script of commands and returns everything it wrote.
+fn run_session(script: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_go-gtp"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Should have started the engine");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(script.as_bytes())
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(
+        output.status.success(),
+        "Engine exited with {}",
+        output.status
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn scripted_session() {
+    let script = "\
+protocol_version
+1 name
+
+# set up a small board
+boardsize 5
+clear_board
+komi 0.5
+play black C3
+2 play white C3
+play w B3
+genmove black
+3 showboard
+undo
+final_score
+known_command genmove
+known_command fly
+fly
+4 quit
+name
+";
+
+    let result = run_session(script);
+
+    let expected = "\
+= 2
+
+=1 go-gtp
+
+=
+
+=
+
+=
+
+=
+
+?2 illegal move
+
+=
+
+= C4
+
+=3
+   A B C D E
+ 5 - - - - - 5
+ 4 - - B - - 4
+ 3 - W B - - 3
+ 2 - - - - - 2
+ 1 - - - - - 1
+   A B C D E
+Captured by black: 0
+Captured by white: 0
+
+=
+
+= W+0.5
+
+= true
+
+= false
+
+? unknown command
+
+=4
+
+";
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn list_commands_are_all_known() {
+    let result = run_session("list_commands\n");
+
+    let commands: Vec<&str> = result
+        .trim_start_matches("= ")
+        .lines()
+        .take_while(|line| !line.is_empty())
+        .collect();
+    assert_eq!(commands.len(), 14);
+
+    let script: String = commands
+        .iter()
+        .map(|command| format!("known_command {command}\n"))
+        .collect();
+    let result = run_session(&script);
+    assert_eq!(result, "= true\n\n".repeat(commands.len()));
+}
+
+#[test]
+fn self_play_ends_in_a_score() {
+    let mut script = String::from("boardsize 5\nkomi 0.5\n");
+    for _ in 0..40 {
+        script.push_str("genmove b\ngenmove w\n");
+    }
+    script.push_str("final_score\n");
+
+    let result = run_session(&script);
+
+    let score = result.trim_end().rsplit("\n\n").next().unwrap();
+    assert!(
+        score.starts_with("= B+") || score.starts_with("= W+"),
+        "Unexpected score {score:?}"
+    );
+    assert!(
+        !result.contains('?'),
+        "Engine rejected its own move:\n{result}"
+    );
+}